@@ -49,6 +49,8 @@ pub fn update_actor(entity: Entity, a: &Actor, texture_map: &TextureMap, updater
 }
 
 fn get_text_actor(a: &Actor) -> Option<Text> {
+    let (r, g, b) = a.team.colour();
+
     return a
         .activations
         .iter()
@@ -59,7 +61,7 @@ fn get_text_actor(a: &Actor) -> Option<Text> {
         })
         .map(|txt| Text {
             txt,
-            background: Some((203, 201, 200, 175)),
+            background: Some((r, g, b, 175)),
             align: crate::ui::Align::MidCenter,
             offset: Some((0, 32)),
             ..Default::default()
@@ -76,6 +78,11 @@ fn activation_str(a: &Activation) -> String {
 }
 
 fn format_card(card: &Card) -> String {
+    if card.value == 0 {
+        // a cursed card (see CardSet::Cursed)
+        return "X".to_string();
+    }
+
     format!(
         "{}{}",
         card.value,
@@ -84,6 +91,7 @@ fn format_card(card: &Card) -> String {
             Suite::MentalStr => "H",
             Suite::PhysicalAg => "S",
             Suite::MentalAg => "D",
+            Suite::Any => "W",
             _ => panic!("Suite not allowed: {:?}", card.suite),
         }
     )
@@ -158,8 +166,10 @@ fn append_status_icons(sprites: &mut Vec<SpriteConfig>, a: &Actor, texture_map:
     let icon_space = 16;
     let icon_offset = (icons.len() as i32 - 1) * icon_space / 2;
 
-    for (i, icon_name) in icons.iter().enumerate() {
-        let mut icon = texture_map.get(*icon_name).unwrap().clone();
+    let icons = icons.iter().filter_map(|name| texture_map.get(*name));
+
+    for (i, icon) in icons.enumerate() {
+        let mut icon = icon.clone();
         let xpos = i as i32 * icon_space - icon_offset;
         icon.offset = (xpos, 16);
         sprites.push(icon)
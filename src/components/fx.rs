@@ -61,6 +61,23 @@ impl FxSequence {
             .collect()
     }
 
+    /// Skips all visual effects and returns only the final changes to the
+    /// game state (e.g. for simulations where nobody is watching)
+    pub fn into_state_changes(mut self) -> Vec<FxEffect> {
+        let mut result: Vec<FxEffect> = vec![];
+
+        self.1.sort_by_key(|(wait, _)| *wait);
+
+        for (_, eff) in self.1.drain(..) {
+            if let Some(id) = eff.changed_object() {
+                result.retain(|other| other.changed_object() != Some(id));
+                result.push(eff);
+            }
+        }
+
+        result
+    }
+
     // pub fn debug(&self) {
     //     println!("[DEBUG FxSequence] (length: {})", self.1.len());
     //     for (d, fx) in self.1.iter() {
@@ -78,6 +95,10 @@ impl Fx {
         self.0 + self.1.duration()
     }
 
+    pub fn now(eff: FxEffect) -> Self {
+        Self(Instant::now(), eff)
+    }
+
     pub fn run(self, world: &World) {
         let (entities, updater): (Entities, Read<LazyUpdate>) = world.system_data();
 
@@ -89,6 +110,8 @@ impl Fx {
 pub enum FxEffect {
     Update(Actor),
 
+    UpdateStructure(Structure),
+
     Remove(ID),
 
     /// - ID: the target game object
@@ -179,6 +202,25 @@ impl FxEffect {
             .build()
     }
 
+    /// Something crashes into something else (e.g. an actor into a wall)
+    pub fn impact(p: WorldPos) -> Self {
+        let txt = Text {
+            txt: "Crash!".to_string(),
+            font: FontFace::Big,
+            padding: 5,
+            align: Align::MidCenter,
+            color: (21, 22, 23, 255),
+            ..Default::default()
+        };
+
+        FxBuilder::new(p, 400)
+            .sprite("fx-impact-1")
+            .text(txt)
+            .scale(0.5, 1.5)
+            .fade_out()
+            .build()
+    }
+
     pub fn blood_splatter(p: WorldPos) -> Self {
         FxEffect::BloodSplatter(p)
     }
@@ -187,6 +229,17 @@ impl FxEffect {
         FxBuilder::new(p, d)
     }
 
+    /// Returns the id of the game object if this effect changes the state of
+    /// the game (and not just the visuals)
+    pub fn changed_object(&self) -> Option<ID> {
+        match self {
+            FxEffect::Update(a) => Some(a.id),
+            FxEffect::UpdateStructure(s) => Some(s.id),
+            FxEffect::Remove(id) => Some(*id),
+            _ => None,
+        }
+    }
+
     pub fn duration(&self) -> Duration {
         let millis = match self {
             FxEffect::BloodSplatter(..) => 1000,
@@ -275,12 +328,13 @@ impl<'a> System<'a> for FxSystem {
         Entities<'a>,
         ReadStorage<'a, Fx>,
         ReadStorage<'a, ActorCmp>,
+        ReadStorage<'a, StructureCmp>,
         Read<'a, LazyUpdate>,
         Read<'a, TextureMap>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, fx, actors, updater, texture_map) = data;
+        let (entities, fx, actors, structures, updater, texture_map) = data;
         let now = Instant::now();
 
         for (e, Fx(start_time, fx_eff)) in (&entities, &fx).join() {
@@ -295,6 +349,14 @@ impl<'a> System<'a> for FxSystem {
                     handle_update_visual(go, &entities, &actors, &updater, &texture_map)
                 }
 
+                FxEffect::UpdateStructure(s) => {
+                    for (e, StructureCmp(other)) in (&entities, &structures).join() {
+                        if other.id == s.id {
+                            update_structure(e, s, &texture_map, &updater);
+                        }
+                    }
+                }
+
                 FxEffect::Remove(id) => {
                     if let Some(e) = find_entity_by_id(*id, &entities, &actors) {
                         // _ = entities.delete(e);
@@ -2,6 +2,7 @@ mod actors;
 mod animation;
 mod fx;
 mod sprites;
+mod structures;
 
 use crate::FontFace;
 use std::num::NonZeroU8;
@@ -13,19 +14,21 @@ use specs::prelude::{
 };
 use specs_derive::Component;
 
-use crate::core::{Actor, DisplayStr, Obstacle, SpriteConfig, WorldPos};
+use crate::core::{Actor, DisplayStr, Obstacle, SpriteConfig, Structure, WorldPos};
 use crate::ui::{Align, ScreenPos, ScreenText};
 
 pub use crate::components::actors::*;
 pub use crate::components::animation::*;
 pub use crate::components::fx::*;
 pub use crate::components::sprites::*;
+pub use crate::components::structures::*;
 
 pub fn register(world: &mut World) {
     world.register::<Text>();
     world.register::<EndOfLive>();
     world.register::<DelayedSpawn>();
     world.register::<ActorCmp>();
+    world.register::<StructureCmp>();
     world.register::<ObstacleCmp>();
     world.register::<Position>();
 
@@ -54,6 +57,10 @@ pub struct Position(pub WorldPos);
 #[storage(VecStorage)]
 pub struct ActorCmp(pub Actor);
 
+#[derive(Component, Debug)]
+#[storage(VecStorage)]
+pub struct StructureCmp(pub Structure);
+
 #[derive(Debug, Clone)]
 pub struct HitArea {
     obstacle: Obstacle,
@@ -122,6 +129,18 @@ impl Hitbox {
         }
     }
 
+    /// Something to hide behind but nothing that stops a determined attacker
+    pub fn new_low_cover() -> Self {
+        Self {
+            inner: None,
+            outer: HitArea {
+                obstacle: Obstacle::Impediment(NonZeroU8::new(60).unwrap(), 2),
+                offset: (-0.4, -0.4),
+                dim: (0.8, 0.8),
+            },
+        }
+    }
+
     pub fn new_normal_actor() -> Self {
         Self {
             inner: None,
@@ -0,0 +1,27 @@
+use specs::prelude::*;
+
+use crate::components::{Position, Sprites, StructureCmp, ZLayerGameObject};
+use crate::core::{Structure, TextureMap};
+
+pub fn insert_structure(s: &Structure, entities: &Entities, updater: &LazyUpdate) -> Entity {
+    let e = updater.create_entity(entities).build();
+
+    updater.insert(e, Position(s.pos));
+    updater.insert(e, ZLayerGameObject);
+
+    e
+}
+
+pub fn update_structure(
+    entity: Entity,
+    s: &Structure,
+    texture_map: &TextureMap,
+    updater: &LazyUpdate,
+) {
+    if let Some(sprite) = texture_map.get(s.visual()) {
+        updater.insert(entity, Sprites::new(vec![sprite.clone()]));
+    }
+
+    updater.insert(entity, s.obstacle());
+    updater.insert(entity, StructureCmp(s.clone()));
+}
@@ -2,16 +2,23 @@ use std::collections::HashMap;
 
 use crate::components::{FxEffect, FxSequence};
 use crate::core::ai::{attack_vector, AttackVector};
-use crate::core::{DisplayStr, MapPos, Path, WorldPos};
+use crate::core::{LogEvent, MapPos, Path, WorldPos};
 
-use super::actors::{Actor, AttackFx, AttackOption, AttackTarget, CombatResult, Hit, Wound, ID};
+use super::actors::{
+    Activation, Actor, AidOption, AttackFx, AttackOption, AttackTarget, CombatResult, Hit, Wound, ID,
+};
 use super::ai::find_charge_path;
-use super::{resolve_combat_new, Card, CoreWorld, Deck, Impact, SuperLineIter, TeamId};
+use super::{
+    resolve_combat_new, Card, CoreWorld, Deck, Impact, SuperLineIter, TeamId, TileType,
+};
 
 #[derive(Debug, Clone)]
 pub enum Action {
     StartTurn(ID),
     BoostActivation(ID, Card),
+    /// Puts the activations of an actor back the way they were (e.g. to take
+    /// back a card assignment)
+    ResetActivations(ID, Vec<Activation>),
     ActivateActor(ID),
     DoNothing(ID),
 
@@ -28,6 +35,12 @@ pub enum Action {
         msg: String,
     },
 
+    Aid {
+        actor: ID,
+        target: ID,
+        aid: AidOption,
+    },
+
     AddTrait {
         targets: Vec<ID>,
         trait_ref: String,
@@ -42,7 +55,7 @@ pub enum Action {
 pub struct ActionResultBuilder<'a> {
     pub world: CoreWorld<'a>,
     pub fx_seq: FxSequence,
-    pub log: Option<DisplayStr>,
+    pub log: Vec<LogEvent>,
     pub score: u64,
 }
 
@@ -51,15 +64,19 @@ impl<'a> ActionResultBuilder<'a> {
         Self {
             world,
             fx_seq: FxSequence::new(),
-            log: None,
+            log: vec![],
             score: 0,
         }
     }
 
     fn into_result(self) -> ActionResult {
-        let (decks, updates) = self.world.into_changes();
+        let (decks, updates, structure_updates) = self.world.into_changes();
         let mut fx_seq = FxSequence::new();
 
+        for s in structure_updates {
+            fx_seq = fx_seq.then(FxEffect::UpdateStructure(s));
+        }
+
         for (id, a) in updates {
             if let Some(go) = a {
                 fx_seq = fx_seq.then(FxEffect::Update(go));
@@ -81,12 +98,9 @@ impl<'a> ActionResultBuilder<'a> {
         M: FnOnce(CoreWorld<'a>) -> Self,
     {
         let new_result = f(self.world);
-        let log = match (self.log, new_result.log) {
-            (Some(l), None) => Some(l),
-            (None, Some(l)) => Some(l),
-            (Some(l1), Some(l2)) => Some(DisplayStr::new(format!("{}\n{}", l1, l2))),
-            _ => None,
-        };
+        let mut log = self.log;
+
+        log.extend(new_result.log);
 
         Self {
             world: new_result.world,
@@ -106,13 +120,8 @@ impl<'a> ActionResultBuilder<'a> {
         self
     }
 
-    fn append_log(mut self, other_log: Option<DisplayStr>) -> Self {
-        self.log = match (self.log, other_log) {
-            (Some(l), None) => Some(l),
-            (None, Some(l)) => Some(l),
-            (Some(l1), Some(l2)) => Some(DisplayStr::new(format!("{}\n{}", l1, l2))),
-            _ => None,
-        };
+    fn append_log(mut self, other_log: Vec<LogEvent>) -> Self {
+        self.log.extend(other_log);
         self
     }
 
@@ -125,7 +134,7 @@ impl<'a> ActionResultBuilder<'a> {
 pub struct ActionResult {
     pub decks: Option<HashMap<TeamId, Deck>>,
     pub fx_seq: FxSequence,
-    pub log: Option<DisplayStr>,
+    pub log: Vec<LogEvent>,
     pub score: u64,
 }
 
@@ -150,6 +159,14 @@ pub fn run_player_action<'a>(action: Action, mut cw: CoreWorld) -> ActionResult
             ActionResultBuilder::new(cw)
         }
 
+        Action::ResetActivations(actor_id, activations) => {
+            cw.modify_actor(actor_id, |mut a| {
+                a.activations = activations.clone();
+                a
+            });
+            ActionResultBuilder::new(cw)
+        }
+
         Action::ActivateActor(id) => {
             if let Some(actor) = cw.find_actor(|a| a.active) {
                 cw.modify_actor(actor.id, |a| a.deactivate());
@@ -174,6 +191,8 @@ pub fn run_player_action<'a>(action: Action, mut cw: CoreWorld) -> ActionResult
             ..
         } => handle_attack(attacker, target, attack, cw),
 
+        Action::Aid { actor, target, aid } => handle_aid(actor, target, aid, cw),
+
         Action::AddTrait {
             targets, trait_ref, ..
         } => handle_add_trait(targets, trait_ref, cw),
@@ -200,6 +219,7 @@ fn filter_attack_vector(input: &AttackVector, w: &CoreWorld) -> Vec<AttackTarget
                 pos,
                 cover,
                 actor: id.and_then(|id| w.get_actor(id).cloned()),
+                structure: id.and_then(|id| w.get_structure(id).cloned()),
                 is_target,
             });
 
@@ -379,17 +399,17 @@ fn handle_attack<'a>(
     mut cw: CoreWorld<'a>,
 ) -> ActionResultBuilder<'a> {
     let attacker = cw.get_actor(attacker_id).cloned();
-    let target = cw.get_actor(target_id).cloned();
+    let target_pos = target_pos(target_id, &cw);
 
-    if attacker.is_none() || target.is_none() {
+    if attacker.is_none() || target_pos.is_none() {
         return ActionResultBuilder::new(cw);
     }
 
     let mut attacker = attacker.unwrap();
-    let target = target.unwrap();
+    let target_pos = target_pos.unwrap();
     let max_distance = attacker.move_distance() + attack.max_distance;
     let from = MapPos::from_world_pos(attacker.pos);
-    let to = MapPos::from_world_pos(target.pos);
+    let to = MapPos::from_world_pos(target_pos);
     let d = from.distance(to);
 
     if !attacker.is_concious() {
@@ -402,7 +422,7 @@ fn handle_attack<'a>(
 
     let advance_distance = d.checked_sub(attack.max_distance.into()).unwrap_or(0);
     let partial_result = if advance_distance > 0 {
-        let path = find_charge_path(&attacker, &target, &cw);
+        let path = find_charge_path(&attacker, target_pos, &cw);
         if path.is_none() {
             return ActionResultBuilder::new(cw)
                 .add_fx(FxEffect::say("The way is blocked!", attacker.pos));
@@ -422,7 +442,11 @@ fn handle_attack<'a>(
             .then(FxEffect::dust("fx-dust-1", pos_start, 400))
             .then(FxEffect::move_along(attacker_id, vec![pos_start, pos_end]));
 
-        ActionResultBuilder::new(cw).append_fx_seq(charge_fx)
+        // the charge rushes over every tile of the way (e.g. through hazards)
+        path.iter().skip(1).take(advance_distance).fold(
+            ActionResultBuilder::new(cw).append_fx_seq(charge_fx),
+            |result, t| result.chain(|w| enter_tile(attacker_id, t.to_map_pos(), w)),
+        )
     } else {
         ActionResultBuilder::new(cw)
     };
@@ -442,9 +466,13 @@ fn perform_attack(
     attack_option: AttackOption,
     mut cw: CoreWorld,
 ) -> ActionResultBuilder {
-    let attacker = cw.get_actor(attacker).cloned().unwrap();
-    let target = cw.get_actor(target).cloned().unwrap();
-    let v = attack_vector(&attacker, &target, &attack_option, &cw);
+    let attacker = match cw.get_actor(attacker) {
+        Some(a) if a.is_concious() => a.clone(),
+        // e.g. the attacker has been hurt on the way to the target
+        _ => return ActionResultBuilder::new(cw),
+    };
+    let target_pos = target_pos(target, &cw).unwrap();
+    let v = attack_vector(&attacker, target_pos, &attack_option, &cw);
 
     // println!("\nATTACK VECTOR {:?}", v);
 
@@ -456,7 +484,9 @@ fn perform_attack(
 
     let v = v.unwrap();
     let attack_end_pos = v.last().unwrap().0.to_world_pos();
-    let attack = attack_option.into_attack(&attacker);
+    let mut attack = attack_option.into_attack(&attacker);
+    attack.advantage = height_advantage(attacker.pos, target_pos, &cw);
+
     let attack_targets = filter_attack_vector(&v, &cw);
 
     // println!("Targets {:?}", attack_targets);
@@ -467,11 +497,11 @@ fn perform_attack(
     // let combat_result = resolve_combat(&attack, attack_targets);
     let combat_fx_seq = create_combat_fx(&attacker, attack_end_pos, &combat_result);
 
-    let mut result = ActionResultBuilder::new(cw)
-        .append_fx_seq(combat_fx_seq)
-        .append_log(DisplayStr::new("TODO").into());
+    let mut result = ActionResultBuilder::new(cw).append_fx_seq(combat_fx_seq);
 
     for h in combat_result.hits {
+        result = result.append_log(h.log);
+
         for eff in h.effects {
             result = result.chain(|w| apply_hit_effect(eff, w))
         }
@@ -480,6 +510,32 @@ fn perform_attack(
     result
 }
 
+/// The position of the attacked actor or structure
+fn target_pos(target: ID, cw: &CoreWorld) -> Option<WorldPos> {
+    cw.get_actor(target)
+        .map(|a| a.pos)
+        .or_else(|| cw.get_structure(target).map(|s| s.pos))
+}
+
+/// Attacking from high ground grants an advantage (and attacking someone on
+/// high ground a disadvantage)
+fn height_advantage(attacker_pos: WorldPos, target_pos: WorldPos, cw: &CoreWorld) -> i8 {
+    let is_on_high_ground = |p: WorldPos| {
+        matches!(
+            cw.map()
+                .get_tile(MapPos::from_world_pos(p))
+                .map(|t| t.tile_type()),
+            Some(TileType::HighGround)
+        )
+    };
+
+    match (is_on_high_ground(attacker_pos), is_on_high_ground(target_pos)) {
+        (true, false) => 1,
+        (false, true) => -1,
+        _ => 0,
+    }
+}
+
 fn apply_hit_effect(eff: Impact, mut cw: CoreWorld) -> ActionResultBuilder {
     match eff {
         Impact::Block(mpos, id) => {
@@ -511,12 +567,34 @@ fn apply_hit_effect(eff: Impact, mut cw: CoreWorld) -> ActionResultBuilder {
                 .append_fx_seq(fx_seq)
         }
 
+        Impact::Damage(dmg, id) => {
+            let mut fx_seq = FxSequence::new();
+
+            if let Some(s) = cw.get_structure(id).cloned() {
+                let was_destroyed = s.is_destroyed();
+                let s = s.damage(dmg);
+
+                fx_seq = if s.is_destroyed() && !was_destroyed {
+                    fx_seq
+                        .then(FxEffect::dust("fx-dust-1", s.pos, 800))
+                        .then(FxEffect::scream("Crash!", s.pos))
+                } else {
+                    fx_seq.then(FxEffect::say("Crack!", s.pos))
+                };
+
+                cw.update_structure(s);
+            }
+
+            ActionResultBuilder::new(cw).append_fx_seq(fx_seq)
+        }
+
         Impact::ForceMove {
             id,
             dx,
             dy,
             distance,
-        } => force_move(id, dx, dy, distance, cw),
+            violent,
+        } => force_move(id, dx, dy, distance, violent, cw),
 
         _ => ActionResultBuilder::new(cw),
     }
@@ -555,40 +633,115 @@ fn take_step(actor_id: ID, target_pos: WorldPos, cw: CoreWorld) -> ActionResultB
     move_to(actor.clone(), target_pos, cw, true)
 }
 
-fn force_move(actor_id: ID, dx: i32, dy: i32, distance: u8, cw: CoreWorld) -> ActionResultBuilder {
+fn force_move(
+    actor_id: ID,
+    dx: i32,
+    dy: i32,
+    distance: u8,
+    violent: bool,
+    cw: CoreWorld,
+) -> ActionResultBuilder {
     let actor = cw.get_actor(actor_id);
     if actor.is_none() {
         // moving actor may have been killed by now
         return ActionResultBuilder::new(cw);
     }
 
-    let actor = actor.unwrap();
+    let actor = actor.unwrap().clone();
     let p1 = MapPos::from_world_pos(actor.pos);
     let p2 = MapPos(p1.0 + dx, p1.1 + dy);
+    if p1 == p2 {
+        return ActionResultBuilder::new(cw);
+    }
+
     let obstacles = cw.collect_obstacles();
     let mut target_pos = p1;
+    let mut collision = None;
     let mut positions_along = SuperLineIter::new(p1, p2);
 
     // ignore first pos because that is where the actor already is
     let _ = positions_along.next();
 
     for p in positions_along {
-        if let Some(_) = obstacles.get(&p) {
-            // stopped by an obstancle
+        if p1.distance(p) > distance.into() {
             break;
         }
 
-        if p1.distance(p) > distance.into() {
+        if let Some((_, id)) = obstacles.get(&p) {
+            // stopped by an obstancle
+            collision = Some((p, *id));
             break;
         }
 
-        target_pos = p;
+        let tile_type = cw.map().get_tile(p).map(|t| t.tile_type());
+
+        match tile_type {
+            None | Some(TileType::Void) | Some(TileType::Pit) => {
+                if !actor.is_flying() {
+                    // pushed over the edge
+                    // => there is nothing left to stop the fall
+                    target_pos = p;
+                }
+                break;
+            }
+
+            _ => {
+                target_pos = p;
+            }
+        }
     }
 
-    if p1.distance(target_pos) == 0 {
+    let result = if p1.distance(target_pos) == 0 {
         ActionResultBuilder::new(cw)
     } else {
-        move_to(actor.clone(), target_pos.to_world_pos(), cw, false)
+        move_to(actor, target_pos.to_world_pos(), cw, false)
+    };
+
+    match collision {
+        Some((obstacle_pos, obstacle_id)) if violent => {
+            result.chain(|w| crash_into(actor_id, obstacle_pos, obstacle_id, w))
+        }
+
+        _ => result,
+    }
+}
+
+/// Handles an actor being slammed into an obstacle (or another actor)
+fn crash_into(
+    actor_id: ID,
+    obstacle_pos: MapPos,
+    obstacle_id: Option<ID>,
+    cw: CoreWorld,
+) -> ActionResultBuilder {
+    let actor = cw.get_actor(actor_id);
+    if actor.is_none() {
+        return ActionResultBuilder::new(cw);
+    }
+
+    let p1 = actor.unwrap().pos;
+    let p2 = obstacle_pos.to_world_pos();
+    let impact_pos = WorldPos::new((p1.x() + p2.x()) / 2.0, (p1.y() + p2.y()) / 2.0, 0.0);
+    let result = ActionResultBuilder::new(cw).add_fx(FxEffect::impact(impact_pos));
+
+    if let Some(s) = obstacle_id.and_then(|id| result.world.get_structure(id).cloned()) {
+        // slammed into a wall which may crack under the impact
+        let w = Wound { pain: 1, wound: 1 };
+
+        result
+            .chain(|w2| apply_hit_effect(Impact::Wound(w, actor_id), w2))
+            .chain(|w2| apply_hit_effect(Impact::Damage(1, s.id), w2))
+    } else if let Some(other_id) = obstacle_id {
+        // colliding with someone else hurts both
+        let w = Wound { pain: 1, wound: 0 };
+
+        result
+            .chain(|w2| apply_hit_effect(Impact::Wound(w.clone(), actor_id), w2))
+            .chain(|w2| apply_hit_effect(Impact::Wound(w, other_id), w2))
+    } else {
+        // slammed into a wall
+        let w = Wound { pain: 1, wound: 1 };
+
+        result.chain(|w2| apply_hit_effect(Impact::Wound(w, actor_id), w2))
     }
 }
 
@@ -620,11 +773,45 @@ fn move_to(
         .wait(move_fx_dur.as_millis() as u64)
         .then(FxEffect::dust("fx-dust-1", target_pos, 400));
 
-    ActionResultBuilder::new(cw).append_fx_seq(fx_seq)
+    ActionResultBuilder::new(cw)
+        .append_fx_seq(fx_seq)
+        .chain(|w| enter_tile(actor_id, MapPos::from_world_pos(target_pos), w))
     // .append_effects(eff_seq)
     // .chain(|w| handle_aoo(actor_id, w))
 }
 
+/// Handles the consequences of stepping onto dangerous terrain (or falling off
+/// the arena)
+fn enter_tile(actor_id: ID, pos: MapPos, mut cw: CoreWorld) -> ActionResultBuilder {
+    let actor = cw.get_actor(actor_id).cloned();
+    if actor.is_none() {
+        return ActionResultBuilder::new(cw);
+    }
+
+    let actor = actor.unwrap();
+    let tile_type = cw.map().get_tile(pos).map(|t| t.tile_type());
+
+    match tile_type {
+        None | Some(TileType::Void) | Some(TileType::Pit) if !actor.is_flying() => {
+            let fx_seq = FxSequence::new()
+                .then(FxEffect::scream("Aaaaah...", actor.pos))
+                .then(FxEffect::Remove(actor_id));
+
+            cw.remove(actor_id);
+
+            ActionResultBuilder::new(cw)
+                .score(100)
+                .append_fx_seq(fx_seq)
+        }
+
+        Some(TileType::Hazard) if !actor.is_flying() && !actor.is_underground() => {
+            apply_hit_effect(Impact::Wound(Wound { pain: 1, wound: 1 }, actor_id), cw)
+        }
+
+        _ => ActionResultBuilder::new(cw),
+    }
+}
+
 // /// Check for attacks of opportunities (aoo)
 // fn handle_aoo(moving_actor_id: ID, mut world: CoreWorld) -> ActionResultBuilder {
 //     if let Some((attacker, attack)) = world.find_map(|go| can_attack(go, moving_actor_id, &world)) {
@@ -714,6 +901,50 @@ fn post_attack_cleanup(_attacked_target: ID, cw: CoreWorld) -> ActionResultBuild
     ActionResultBuilder::new(cw)
 }
 
+fn handle_aid<'a>(
+    actor_id: ID,
+    target_id: ID,
+    aid: AidOption,
+    mut cw: CoreWorld<'a>,
+) -> ActionResultBuilder<'a> {
+    let actor = cw.get_actor(actor_id).cloned();
+    let target = cw.get_actor(target_id).cloned();
+
+    if actor.is_none() || target.is_none() {
+        return ActionResultBuilder::new(cw);
+    }
+
+    let actor = actor.unwrap();
+    let target = target.unwrap();
+    let from = MapPos::from_world_pos(actor.pos);
+    let to = MapPos::from_world_pos(target.pos);
+
+    if !actor.is_concious() {
+        return ActionResultBuilder::new(cw).add_fx(FxEffect::say("Ahhh", actor.pos));
+    }
+
+    if from.distance(to) > aid.max_distance.into() {
+        return ActionResultBuilder::new(cw).add_fx(FxEffect::say("It's too far!", actor.pos));
+    }
+
+    let was_concious = target.is_concious();
+    let target = aid
+        .effects
+        .iter()
+        .fold(target, |t, eff| t.receive_aid(eff));
+
+    let fx_seq = if !was_concious && target.is_concious() {
+        FxSequence::new().then(FxEffect::scream("Back on your feet!", target.pos))
+    } else {
+        FxSequence::new().then(FxEffect::say("Patched up", target.pos))
+    };
+
+    cw.update(target);
+    cw.modify_actor(actor_id, Actor::done);
+
+    ActionResultBuilder::new(cw).append_fx_seq(fx_seq)
+}
+
 fn handle_add_trait(
     targets: Vec<ID>,
     trait_ref: String,
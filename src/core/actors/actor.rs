@@ -1,38 +1,68 @@
 use std::cmp::{max, min};
 use std::collections::HashMap;
-use std::time::Instant;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 pub use super::traits::AttributeModifier::*;
 pub use super::traits::*;
 
 use super::ActorTemplateName;
 
-use crate::core::{Card, Deck, DisplayStr, MapPos, Suite, WorldPos};
+use crate::core::{
+    resolve_challenge, with_rng, Card, Challenge, Deck, DeckChange, DeckName, DisplayStr, MapPos,
+    Suite, WorldPos,
+};
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-pub struct ID(Instant, u64, u64);
+/// The target number an actor has to beat with its mental strength to shake
+/// off pain at the start of a turn
+const PAIN_RECOVERY_TN: u8 = 10;
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ID(u64, u64);
 
 impl ID {
     pub fn new() -> Self {
-        use rand::random;
-        Self(Instant::now(), random(), random())
+        use rand::Rng;
+        with_rng(|rng| Self(rng.gen(), rng.gen()))
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum AiBehaviour {
     Default,
+    /// Charges the closest enemy no matter what
+    Berserker,
+    /// Keeps its distance and attacks from afar
+    Skirmisher,
+    /// Protects its allies
+    Guardian,
+    /// Lies in wait (preferably underground) until prey comes close
+    Ambusher,
+    /// Runs for its life when hurt
+    Coward,
+    /// Weighs up all options; the higher the temperature the more likely it
+    /// picks a less than optimal one
+    Tactician { temperature: f32 },
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct TeamId(u8);
 
 impl TeamId {
     pub fn new(raw_id: u8) -> Self {
         Self(raw_id)
     }
+
+    /// The colour used to tell the teams apart (e.g. in a hot-seat game)
+    pub fn colour(&self) -> (u8, u8, u8) {
+        match self.0 {
+            1 => (62, 112, 203),
+            2 => (203, 62, 62),
+            3 => (62, 163, 80),
+            4 => (222, 190, 42),
+            _ => (203, 201, 200),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -40,9 +70,27 @@ pub struct Team {
     pub name: &'static str,
     pub id: TeamId,
     pub is_pc: bool,
+    /// The team is controlled by a player on another machine
+    pub is_remote: bool,
+    /// How the team regards the other teams (those not listed are hostile)
+    pub relations: Vec<(TeamId, Relation)>,
+    /// The deck the team starts with
+    pub deck: DeckName,
+    /// Cards the team has won (or lost) before the combat (e.g. as a reward)
+    pub deck_changes: Vec<DeckChange>,
     pub reinforcements: Option<Vec<(u64, MapPos, ActorTemplateName)>>,
 }
 
+/// The diplomatic relation between two teams
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Relation {
+    /// Fight side by side and never attack each other
+    Allied,
+    /// Leave each other alone (until someone attacks)
+    Neutral,
+    Hostile,
+}
+
 impl Team {
     pub fn is_member(&self, a: &Actor) -> bool {
         self.id == a.team
@@ -230,7 +278,7 @@ impl Visual {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Activation {
     Single(Card),
     Boosted(Card, Card),
@@ -337,6 +385,16 @@ impl Actor {
         self
     }
 
+    /// Checks if there is an activation left to boost (or if the actor may
+    /// still get its one bonus activation)
+    pub fn can_be_boosted(&self) -> bool {
+        self.activations.len() <= self.max_activations as usize
+            || self
+                .activations
+                .iter()
+                .any(|a| matches!(a, Activation::Single(..)))
+    }
+
     pub fn boost_activation(mut self, new_card: Card) -> Self {
         if let Some((idx, activation)) = self.activations.iter().enumerate().find_map(|(idx, a)| {
             if let Activation::Boosted(..) = a {
@@ -420,6 +478,10 @@ impl Actor {
         }
         .process_traits();
 
+        if result.health.pain > 0 {
+            result = result.recover_from_pain(deck);
+        }
+
         for _ in 1..=result.num_activation() {
             let card = if result.has_keyword(Keyword::Quick) {
                 // Quick actors draw two cards and discard the higher one
@@ -503,6 +565,25 @@ impl Actor {
         }
     }
 
+    pub fn aids(&self) -> Vec<AidOption> {
+        self.effects
+            .iter()
+            .filter_map(|(_, eff)| match eff {
+                Effect::Aid {
+                    name,
+                    distance_max,
+                    effects,
+                } => Some(AidOption {
+                    name: name.clone(),
+                    max_distance: distance_max.unwrap_or(1),
+                    effects: effects.clone(),
+                }),
+
+                _ => None,
+            })
+            .collect()
+    }
+
     fn process_traits(mut self) -> Self {
         let mut effects = vec![];
         let mut keywords = 0;
@@ -546,6 +627,22 @@ impl Actor {
         ActiveTraitIter(self.traits.values())
     }
 
+    /// The changes the traits of the actor make to the deck of its team
+    /// (ordered by trait so every peer of a networked game builds the same deck)
+    pub fn deck_changes(&self) -> Vec<DeckChange> {
+        let mut traits = self.traits.iter().collect::<Vec<_>>();
+        traits.sort_by_key(|(key, _)| *key);
+
+        traits
+            .iter()
+            .flat_map(|(_, t)| t.effects.iter())
+            .filter_map(|e| match e {
+                Effect::Deck(c) => Some(c.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
     ////////////////////////////////////////////////////////////
     // Health
 
@@ -560,6 +657,43 @@ impl Actor {
         }
     }
 
+    /// Returns true if at least one effect of the given aid would make a difference
+    pub fn can_be_aided_with(&self, aid: &AidOption) -> bool {
+        self.is_alive()
+            && aid.effects.iter().any(|eff| match eff {
+                AidEffect::Heal(_) => self.health.recieved_wounds > 0,
+                AidEffect::Stabilize(_) => self.health.pain > 0,
+                AidEffect::Revive => !self.is_concious(),
+            })
+    }
+
+    pub fn receive_aid(mut self, eff: &AidEffect) -> Self {
+        if self.is_alive() {
+            self.health = self.health.aid(eff);
+        }
+        self
+    }
+
+    /// Actors in pain may shake some of it off at the start of a turn; the
+    /// stronger their will the quicker they recover
+    fn recover_from_pain(mut self, deck: &mut Deck) -> Self {
+        let result = resolve_challenge(
+            Challenge {
+                advantage: 0,
+                challenge_type: Suite::MentalStr,
+                skill_val: self.skill(Suite::MentalStr, 0),
+                target_num: PAIN_RECOVERY_TN,
+            },
+            deck,
+        );
+
+        if result.success_lvl > 0 {
+            self.health = self.health.aid(&AidEffect::Stabilize(result.success_lvl as u8));
+        }
+
+        self
+    }
+
     pub fn is_alive(&self) -> bool {
         self.health.remaining_wounds > 0
     }
@@ -661,6 +795,26 @@ impl Health {
         self.pain += w.pain;
         self
     }
+
+    fn aid(mut self, eff: &AidEffect) -> Self {
+        match eff {
+            AidEffect::Heal(w) => {
+                self.recieved_wounds = self.recieved_wounds.saturating_sub(*w);
+                self.remaining_wounds = min(self.max_wounds, self.remaining_wounds + w);
+            }
+
+            AidEffect::Stabilize(p) => {
+                self.pain = self.pain.saturating_sub(*p);
+            }
+
+            AidEffect::Revive => {
+                // an actor is concious as long as the pain does not exceed the
+                // remaining wounds
+                self.pain = min(self.pain, self.remaining_wounds);
+            }
+        }
+        self
+    }
 }
 
 pub struct ActiveTraitIter<'a>(std::collections::hash_map::Values<'a, String, Trait>);
@@ -712,6 +866,13 @@ impl AttackOption {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct AidOption {
+    pub name: DisplayStr,
+    pub max_distance: u8,
+    pub effects: Vec<AidEffect>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Attack {
     pub origin_pos: WorldPos,
@@ -733,3 +894,40 @@ pub struct Wound {
     pub pain: u8,
     pub wound: u8,
 }
+
+#[test]
+fn test_aid_can_revive_and_heal_a_knocked_out_actor() {
+    use std::path::Path;
+
+    let generator = super::ObjectGenerator::new(Path::new("assets/data/"));
+    let medic = generator.generate_player(
+        WorldPos::new(0.0, 0.0, 0.0),
+        TeamId::new(0),
+        ActorTemplateName::new("actor#medic"),
+    );
+    let aids = medic.aids();
+    let bandage = aids.iter().find(|a| a.name.to_string() == "Bandage wounds").unwrap();
+    let stimulant = aids.iter().find(|a| a.name.to_string() == "Stimulant shot").unwrap();
+
+    let patient = medic.clone().wound(Wound { pain: 3, wound: 1 });
+    assert!(patient.is_alive());
+    assert!(!patient.is_concious());
+    assert!(patient.can_be_aided_with(bandage));
+    assert!(patient.can_be_aided_with(stimulant));
+
+    let patient = stimulant
+        .effects
+        .iter()
+        .fold(patient, |p, eff| p.receive_aid(eff));
+    assert!(patient.is_concious());
+    assert_eq!(patient.health.pain, 1);
+    assert!(patient.can_be_aided_with(bandage));
+
+    let patient = bandage
+        .effects
+        .iter()
+        .fold(patient, |p, eff| p.receive_aid(eff));
+    assert_eq!(patient.health.recieved_wounds, 0);
+    assert_eq!(patient.health.remaining_wounds, patient.health.max_wounds);
+    assert!(!patient.can_be_aided_with(bandage));
+}
@@ -4,7 +4,10 @@ use std::collections::HashMap;
 use super::actor::*;
 use super::traits::HitEffect as AttackHitEffect;
 
-use crate::core::{resolve_challenge, Challenge, Deck, MapPos, Obstacle, Suite, WorldPos};
+use crate::core::{
+    resolve_challenge, Challenge, ChallengeKind, ChallengeResult, Deck, LogEvent, MapPos, Obstacle,
+    Structure, Suite, WorldPos,
+};
 
 #[derive(Debug, Clone)]
 pub struct Cover {
@@ -43,6 +46,7 @@ pub struct AttackTarget {
     pub pos: MapPos,
     pub is_target: bool,
     pub actor: Option<Actor>,
+    pub structure: Option<Structure>,
     pub cover: Cover,
 }
 
@@ -57,6 +61,8 @@ pub struct Hit {
     // pub roll: Roll,
     pub pos: MapPos,
     pub effects: Vec<Impact>,
+    /// How the hit came about (for the combat log)
+    pub log: Vec<LogEvent>,
 }
 
 #[derive(Clone, Debug)]
@@ -67,11 +73,17 @@ pub enum Impact {
 
     Wound(Wound, ID),
 
+    /// Damage to a structure (e.g. a wall)
+    Damage(u8, ID),
+
     ForceMove {
         id: ID,
         dx: i32,
         dy: i32,
         distance: u8,
+        /// A violent move (e.g. a push) hurts the moved actor (and whatever it
+        /// crashes into) if it is stopped by an obstacle
+        violent: bool,
     },
 }
 
@@ -83,19 +95,28 @@ pub fn resolve_combat_new(
 ) -> CombatResult {
     let mut hits = vec![];
 
-    for t in targets {
-        if let Some(target_actor) = t.actor {
-            if t.is_target {
-                hits.push(resolve_attack(
-                    attack,
-                    attacker,
-                    &target_actor,
-                    decks,
-                    t.pos,
-                ));
-            } else {
-                // accidentally caught in the line of fire
+    for t in targets.iter() {
+        if !t.is_target {
+            // accidentally caught in the line of fire
+            continue;
+        }
+
+        if let Some(target_actor) = &t.actor {
+            let hit = resolve_attack(attack, attacker, target_actor, decks, t.pos);
+            let missed = matches!(hit.effects.as_slice(), [Impact::Miss()]);
+
+            hits.push(hit);
+
+            if missed {
+                // whatever the target was hiding behind takes the hit instead
+                if let Some((pos, s)) = covering_structure(&t.cover, &targets) {
+                    hits.push(resolve_attack_on_structure(attack, attacker, s, decks, pos));
+                }
             }
+        } else if let Some(s) = &t.structure {
+            hits.push(resolve_attack_on_structure(
+                attack, attacker, s, decks, t.pos,
+            ));
         }
     }
 
@@ -112,44 +133,56 @@ pub fn resolve_attack(
     decks: &mut HashMap<TeamId, Deck>,
     pos: MapPos,
 ) -> Hit {
-    let quality = attacker.skill(attack.to_hit.0, attack.to_hit.1)
-        + attack.effort_card.value(attack.to_hit.0);
+    let skill = attacker.skill(attack.to_hit.0, attack.to_hit.1);
+    let quality = skill + attack.effort_card.value(attack.to_hit.0);
+    let mut log = vec![LogEvent::Attack {
+        attacker: attacker.name.clone(),
+        target: target.name.clone(),
+        attack: attack.name.to_string(),
+        skill,
+        effort: attack.effort_card,
+        quality,
+    }];
 
     // STEP defender flips agains the attack to determine if the attack hits
-    let defence_result = resolve_challenge(
-        Challenge {
-            target_num: quality,
-            advantage: 0,
-            challenge_type: attack.defence,
-            skill_val: target.skill(Suite::PhysicalAg, 0),
-        },
-        decks.get_mut(&target.team).unwrap(),
-    );
+    let defence = Challenge {
+        target_num: quality,
+        advantage: -attack.advantage,
+        challenge_type: attack.defence,
+        skill_val: target.skill(Suite::PhysicalAg, 0),
+    };
+    let defence_result = resolve_challenge(defence.clone(), decks.get_mut(&target.team).unwrap());
 
-    println!(
-        "\n[DEBUG COMBAT] {} attacks with '{}': {} (effort: {:?})",
-        attacker.name, attack.name, quality, attack.effort_card
-    );
-    println!(" - {} defends: {:?}", target.name, defence_result);
+    log.push(log_challenge(
+        &target.name,
+        ChallengeKind::Defence,
+        &defence,
+        defence_result.clone(),
+    ));
 
     let effects = if defence_result.success_lvl > 0 {
-        println!(" - no hit");
         vec![Impact::Miss()]
     } else {
-        println!(" - Hit! (advantage: {})", -1 * defence_result.success_lvl);
-
         // STEP attacker flips against defenders armor to determine if/how much
         // damage the attack causes
-        let dmg_result = resolve_challenge(
-            Challenge {
-                advantage: -1 * defence_result.success_lvl,
-                challenge_type: attack.to_wound.0,
-                skill_val: attacker.skill(attack.to_wound.0, attack.to_wound.1),
-                target_num: max(3, target.soak().checked_sub(attack.rend).unwrap_or(0)),
+        let soak = target.soak();
+        let damage = Challenge {
+            advantage: -1 * defence_result.success_lvl,
+            challenge_type: attack.to_wound.0,
+            skill_val: attacker.skill(attack.to_wound.0, attack.to_wound.1),
+            target_num: max(3, soak.checked_sub(attack.rend).unwrap_or(0)),
+        };
+        let dmg_result = resolve_challenge(damage.clone(), decks.get_mut(&attacker.team).unwrap());
+
+        log.push(log_challenge(
+            &attacker.name,
+            ChallengeKind::Damage {
+                soak,
+                rend: attack.rend,
             },
-            decks.get_mut(&attacker.team).unwrap(),
-        );
-        println!(" - check for damage: {:?} ", dmg_result);
+            &damage,
+            dmg_result.clone(),
+        ));
 
         let mut effects = if dmg_result.success_lvl <= -2 {
             // armor more then twice as high as damage
@@ -169,7 +202,94 @@ pub fn resolve_attack(
         effects
     };
 
-    Hit { pos, effects }
+    log.extend(effects.iter().map(|impact| LogEvent::Impact {
+        target: target.name.clone(),
+        impact: impact.clone(),
+    }));
+
+    Hit { pos, effects, log }
+}
+
+fn log_challenge(
+    name: &str,
+    kind: ChallengeKind,
+    c: &Challenge,
+    result: ChallengeResult,
+) -> LogEvent {
+    LogEvent::Challenge {
+        name: name.to_string(),
+        kind,
+        draw: result.draw,
+        skill: c.skill_val,
+        target_num: c.target_num,
+        success_lvl: result.success_lvl,
+    }
+}
+
+fn covering_structure<'a>(
+    cover: &Cover,
+    targets: &'a [AttackTarget],
+) -> Option<(MapPos, &'a Structure)> {
+    let (cover_pos, _, cover_id) = cover.last_obstacle?;
+    let cover_id = cover_id?;
+
+    targets.iter().find_map(|t| match &t.structure {
+        Some(s) if s.id == cover_id => Some((cover_pos, s)),
+        _ => None,
+    })
+}
+
+/// Structures cannot dodge so every attack hits; it only remains to be seen
+/// whether it is strong enough to cause any damage
+pub fn resolve_attack_on_structure(
+    attack: &Attack,
+    attacker: &Actor,
+    target: &Structure,
+    decks: &mut HashMap<TeamId, Deck>,
+    pos: MapPos,
+) -> Hit {
+    let skill = attacker.skill(attack.to_hit.0, attack.to_hit.1);
+    let quality = skill + attack.effort_card.value(attack.to_hit.0);
+    let damage = Challenge {
+        advantage: 0,
+        challenge_type: attack.to_wound.0,
+        skill_val: attacker.skill(attack.to_wound.0, attack.to_wound.1),
+        target_num: max(3, target.armor.saturating_sub(attack.rend)),
+    };
+    let dmg_result = resolve_challenge(damage.clone(), decks.get_mut(&attacker.team).unwrap());
+
+    let effects = if dmg_result.success_lvl < 0 {
+        vec![Impact::Block(pos, target.id)]
+    } else {
+        vec![Impact::Damage(1 + dmg_result.success_lvl as u8, target.id)]
+    };
+
+    let mut log = vec![
+        LogEvent::Attack {
+            attacker: attacker.name.clone(),
+            target: target.name.clone(),
+            attack: attack.name.to_string(),
+            skill,
+            effort: attack.effort_card,
+            quality,
+        },
+        log_challenge(
+            &attacker.name,
+            ChallengeKind::Damage {
+                soak: target.armor,
+                rend: attack.rend,
+            },
+            &damage,
+            dmg_result,
+        ),
+    ];
+
+    log.extend(effects.iter().map(|impact| LogEvent::Impact {
+        target: target.name.clone(),
+        impact: impact.clone(),
+    }));
+
+    Hit { pos, effects, log }
 }
 
 fn add_attack_effects(
@@ -201,6 +321,7 @@ fn convert_attack_hit_effect(
                 dx,
                 dy,
                 distance: *d,
+                violent: true,
             }
         }
 
@@ -212,6 +333,7 @@ fn convert_attack_hit_effect(
                 dx,
                 dy,
                 distance: *d,
+                violent: false,
             }
         }
     }
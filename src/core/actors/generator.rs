@@ -1,6 +1,6 @@
 use std::{collections::HashMap, fs::File, iter::FromIterator, path::Path};
 
-use crate::core::WorldPos;
+use crate::core::{with_rng, Deck, DeckName, DeckStorage, WorldPos};
 
 use super::{
     actor::{Actor, ActorBuilder, AiBehaviour, TeamId, Trait},
@@ -14,6 +14,7 @@ use serde::Deserialize;
 pub struct ObjectGenerator {
     traits: TraitStorage,
     actors: ActorTemplateStorage,
+    decks: DeckStorage,
 }
 
 impl ObjectGenerator {
@@ -21,6 +22,7 @@ impl ObjectGenerator {
         Self {
             traits: TraitStorage::new(path),
             actors: ActorTemplateStorage::new(path),
+            decks: DeckStorage::new(path),
         }
     }
 
@@ -28,6 +30,10 @@ impl ObjectGenerator {
         &self.traits
     }
 
+    pub fn generate_deck(&self, name: &DeckName) -> Deck {
+        self.decks.get(name)
+    }
+
     fn get_trait(&self, key: &str) -> (String, Trait) {
         let t = self.traits.get(key);
         (key.to_string(), t.clone())
@@ -67,13 +73,24 @@ impl ObjectGenerator {
         .visual(visual)
     }
 
+    /// Returns the AI profile configured for the given template (if any)
+    fn behaviour(&self, template_name: &ActorTemplateName) -> AiBehaviour {
+        self.actors
+            .get(template_name)
+            .behaviour
+            .clone()
+            .unwrap_or(AiBehaviour::Default)
+    }
+
     pub fn generate_player(&self, pos: WorldPos, t: TeamId, template: ActorTemplateName) -> Actor {
         self.generate_actor(pos, t, template).build()
     }
 
     pub fn generate_enemy(&self, pos: WorldPos, t: TeamId, template: ActorTemplateName) -> Actor {
+        let behaviour = self.behaviour(&template);
+
         self.generate_actor(pos, t, template)
-            .behaviour(AiBehaviour::Default)
+            .behaviour(behaviour)
             .build()
     }
 }
@@ -94,6 +111,7 @@ struct ActorTemplate {
     attributes: ActorAttriubes,
     traits: Vec<String>,
     visuals: (Vec<VisualConfig>, Vec<(VisualState, Vec<VisualConfig>)>),
+    behaviour: Option<AiBehaviour>,
 }
 
 #[derive(Default)]
@@ -142,7 +160,7 @@ fn between(a: u16, b: u16) -> u16 {
 
 fn one_of<'a, T>(v: &'a Vec<T>) -> &'a T {
     use rand::seq::SliceRandom;
-    v.choose(&mut rand::thread_rng()).unwrap()
+    with_rng(|rng| v.choose(rng)).unwrap()
 }
 
 fn map_visual_config(vcfg: &VisualConfig) -> (VLayers, String) {
@@ -164,6 +182,7 @@ fn test_can_create_all_actors() {
         "actor#saw",
         "actor#spear",
         "actor#gunner",
+        "actor#medic",
         "enemy#sucker",
         "enemy#worm",
         "enemy#zombi",
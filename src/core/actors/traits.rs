@@ -5,7 +5,7 @@ use std::path::Path;
 
 use ron::de::from_reader;
 
-use crate::core::{DisplayStr, Suite};
+use crate::core::{DeckChange, DisplayStr, Suite};
 use serde::Deserialize;
 
 pub const NUM_VISUAL_STATES: usize = 4;
@@ -139,6 +139,15 @@ pub enum Effect {
         fx: String,
     },
 
+    /// A supportive ability (e.g. first aid) which can be used on the actor
+    /// itself or on a team mate
+    Aid {
+        name: DisplayStr,
+        /// The reach of the aid (defaults to 1)
+        distance_max: Option<u8>,
+        effects: Vec<AidEffect>,
+    },
+
     /// (modifier, type)
     Defence(i8, DefenceType),
 
@@ -153,6 +162,9 @@ pub enum Effect {
     GatherStrength,
 
     Keyword(Keyword),
+
+    /// Adds cards to (or removes cards from) the deck of the actor's team
+    Deck(DeckChange),
 }
 
 #[derive(Default)]
@@ -177,6 +189,16 @@ impl TraitStorage {
             }
         };
 
+        for (name, t) in traits.iter() {
+            for e in t.effects.iter() {
+                if let Effect::Deck(DeckChange::Add(card_set) | DeckChange::Remove(card_set)) = e {
+                    if let Err(e) = card_set.validate() {
+                        panic!("Invalid trait {}: {}", name, e);
+                    }
+                }
+            }
+        }
+
         Self {
             traits: HashMap::from_iter(traits),
         }
@@ -202,6 +224,18 @@ pub enum HitEffect {
     PullCloser(u8),
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub enum AidEffect {
+    /// Recovers the given number of wounds (up to the maximum)
+    Heal(u8),
+
+    /// Relieves the given amount of pain
+    Stabilize(u8),
+
+    /// Brings a K.O. actor back to consciousness (barely)
+    Revive,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub enum DefenceType {
     Dodge(u32, u32),
@@ -1,49 +1,56 @@
+mod behaviours;
+mod planning;
 mod primitives;
+mod utility;
 
 // use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::core::*;
+use behaviours::strategy;
 use primitives::*;
+use utility::best_option;
 
+pub use planning::plan_boost;
 pub use primitives::{attack_vector, find_charge_path, AttackVector, PlayerActionOptions};
 
-pub fn determine_actor_action(actor: &Actor, cw: CoreWorld) -> Action {
-    zombi_action(actor, cw)
+/// Determines what the given (AI controlled) actor does next; the AI only
+/// knows about the given enemies
+pub fn determine_actor_action(actor: &Actor, known_enemies: &HashSet<ID>, cw: CoreWorld) -> Action {
+    if !actor.is_concious() {
+        // e.g. knocked out during its own activation
+        return Action::DoNothing(actor.id);
+    }
+
+    let enemies = known_enemies_of(actor, known_enemies, &cw);
+
+    // player characters which have been handed over to the AI
+    let behaviour = actor
+        .behaviour
+        .clone()
+        .unwrap_or(AiBehaviour::Tactician { temperature: 0.0 });
+
+    strategy(&behaviour)(actor, &enemies, &cw)
 }
 
-fn zombi_action(actor: &Actor, cw: CoreWorld) -> Action {
-    for ta in find_enemies(&actor, &cw) {
-        let attacks = possible_attacks(actor, &ta, &cw)
-            .drain(..)
-            .filter(|(_, attack_vector)| !attack_vector.is_empty())
-            .collect::<Vec<_>>();
-
-        if let Some((attack, attack_vector)) = pick_one(attacks) {
-            return Action::Attack {
-                attacker: actor.id,
-                target: ta.id,
-                attack,
-                attack_vector,
-                msg: ta.name,
-            };
-        }
-
-        if let Some(path) = find_path_towards(actor, &ta, &cw) {
-            let p = path
-                .iter()
-                .take(actor.move_distance().into())
-                .cloned()
-                .collect();
-
-            return Action::MoveTo {
-                actor: actor.id,
-                path: p,
-            };
-        }
-    }
+/// Returns the option the AI would choose for a player controlled actor (the
+/// position and the index of the action)
+pub fn suggest_action(
+    actor: &Actor,
+    known_enemies: &HashSet<ID>,
+    options: &PlayerActionOptions,
+    cw: &CoreWorld,
+) -> Option<(MapPos, usize)> {
+    let enemies = known_enemies_of(actor, known_enemies, cw);
+
+    best_option(actor, &enemies, options, cw)
+}
 
-    Action::DoNothing(actor.id)
+fn known_enemies_of(actor: &Actor, known_enemies: &HashSet<ID>, cw: &CoreWorld) -> Vec<Actor> {
+    find_enemies(actor, cw)
+        .drain(..)
+        .filter(|a| known_enemies.contains(&a.id))
+        .collect()
 }
 
 pub fn possible_player_actions(actor: &Actor, cw: &CoreWorld) -> PlayerActionOptions {
@@ -51,6 +58,7 @@ pub fn possible_player_actions(actor: &Actor, cw: &CoreWorld) -> PlayerActionOpt
 
     add_move_to_options(actor, cw, &mut result);
     add_combat_options(actor, cw, &mut result);
+    add_aid_options(actor, cw, &mut result);
 
     result
 }
@@ -64,8 +72,7 @@ fn pick_one<T>(mut list: Vec<T>) -> Option<T> {
     }
 
     let range = rand::distributions::Uniform::from(0..list.len());
-    let mut rng = rand::thread_rng();
-    let idx = rng.sample(range);
+    let idx = with_rng(|rng| rng.sample(range));
 
     Some(list.remove(idx))
 }
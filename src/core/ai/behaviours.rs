@@ -0,0 +1,306 @@
+use crate::core::*;
+
+use super::pick_one;
+use super::primitives::*;
+use super::utility::utility_action;
+
+/// A strategy determines the next action of an AI controlled actor based on
+/// the enemies it knows about (sorted by distance, the closest first)
+pub type Strategy = fn(&Actor, &[Actor], &CoreWorld) -> Action;
+
+pub fn strategy(b: &AiBehaviour) -> Strategy {
+    match b {
+        AiBehaviour::Default => zombi_action,
+        AiBehaviour::Berserker => berserker_action,
+        AiBehaviour::Skirmisher => skirmisher_action,
+        AiBehaviour::Guardian => guardian_action,
+        AiBehaviour::Ambusher => ambusher_action,
+        AiBehaviour::Coward => coward_action,
+        AiBehaviour::Tactician { .. } => utility_action,
+    }
+}
+
+/// Attacks a random enemy within reach or shambles towards the closest one
+fn zombi_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    for ta in enemies {
+        let attacks = usable_attacks(actor, ta, true, cw);
+
+        if let Some((attack, attack_vector)) = pick_one(attacks) {
+            return attack_action(actor, ta, attack, attack_vector);
+        }
+
+        if let Some(action) = advance_towards(actor, ta, 0, cw) {
+            return action;
+        }
+    }
+
+    Action::DoNothing(actor.id)
+}
+
+/// Always goes for the closest enemy with the most brutal attack at hand
+fn berserker_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    for ta in enemies {
+        let attack = usable_attacks(actor, ta, true, cw)
+            .drain(..)
+            .max_by_key(|(attack, _)| attack.rend);
+
+        if let Some((attack, attack_vector)) = attack {
+            return attack_action(actor, ta, attack, attack_vector);
+        }
+
+        if let Some(action) = advance_towards(actor, ta, 0, cw) {
+            return action;
+        }
+    }
+
+    Action::DoNothing(actor.id)
+}
+
+/// Keeps its distance and attacks from afar; backs off if someone gets too
+/// close
+fn skirmisher_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    let preferred_distance = actor
+        .attacks()
+        .iter()
+        .map(|a| a.min_distance as usize)
+        .max()
+        .unwrap_or(0)
+        .max(2);
+
+    let closest_distance = enemies.first().map(|e| distance(actor, e));
+
+    if closest_distance
+        .map(|d| d < preferred_distance)
+        .unwrap_or(false)
+    {
+        if let Some(path) = find_retreat_path(actor, enemies, cw) {
+            return Action::MoveTo {
+                actor: actor.id,
+                path,
+            };
+        }
+    }
+
+    for ta in enemies {
+        if let Some((attack, attack_vector)) = pick_one(usable_attacks(actor, ta, false, cw)) {
+            return attack_action(actor, ta, attack, attack_vector);
+        }
+    }
+
+    enemies
+        .first()
+        .and_then(|ta| advance_towards(actor, ta, preferred_distance, cw))
+        .unwrap_or(Action::DoNothing(actor.id))
+}
+
+/// Stays close to the nearest ally and only fights enemies who come too close
+fn guardian_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    let teams = cw.teams();
+    let ward = cw
+        .actors()
+        .filter(|a| teams.relation(a.team, actor.team) == Relation::Allied && a.id != actor.id)
+        .min_by_key(|a| distance(actor, a))
+        .cloned();
+
+    let guarded_area = ward.as_ref().unwrap_or(actor);
+
+    for ta in enemies.iter().filter(|e| distance(guarded_area, e) <= 2) {
+        if let Some((attack, attack_vector)) = pick_one(usable_attacks(actor, ta, true, cw)) {
+            return attack_action(actor, ta, attack, attack_vector);
+        }
+    }
+
+    ward.and_then(|w| advance_towards(actor, &w, 1, cw))
+        .unwrap_or(Action::DoNothing(actor.id))
+}
+
+/// Strikes only when the prey is within reach; creeps up on it while hidden
+/// underground and lies in wait otherwise
+fn ambusher_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    for ta in enemies {
+        if let Some((attack, attack_vector)) = pick_one(usable_attacks(actor, ta, false, cw)) {
+            return attack_action(actor, ta, attack, attack_vector);
+        }
+    }
+
+    // those who hide underground can only be spotted from right next to them
+    if actor.is_underground() {
+        if let Some(action) = enemies
+            .first()
+            .and_then(|ta| advance_towards(actor, ta, 1, cw))
+        {
+            return action;
+        }
+    }
+
+    Action::DoNothing(actor.id)
+}
+
+/// Fights like everyone else until badly hurt; then it runs
+fn coward_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    if actor.health.remaining_wounds <= 1 {
+        if let Some(path) = find_retreat_path(actor, enemies, cw) {
+            return Action::MoveTo {
+                actor: actor.id,
+                path,
+            };
+        }
+    }
+
+    zombi_action(actor, enemies, cw)
+}
+
+/////////////////////////////////////////////////////////////////////
+// little helper
+
+fn distance(a1: &Actor, a2: &Actor) -> usize {
+    MapPos::from_world_pos(a1.pos).distance(MapPos::from_world_pos(a2.pos))
+}
+
+/// Returns the attacks which can be used against the target (optionally only
+/// those which do not require to advance first)
+fn usable_attacks(
+    actor: &Actor,
+    target: &Actor,
+    allow_advance: bool,
+    cw: &CoreWorld,
+) -> Vec<(AttackOption, AttackVector)> {
+    let d = distance(actor, target);
+
+    possible_attacks(actor, target, cw)
+        .drain(..)
+        .filter(|(_, attack_vector)| !attack_vector.is_empty())
+        .filter(|(attack, _)| allow_advance || d <= attack.max_distance.into())
+        .collect()
+}
+
+fn attack_action(
+    actor: &Actor,
+    target: &Actor,
+    attack: AttackOption,
+    attack_vector: AttackVector,
+) -> Action {
+    Action::Attack {
+        attacker: actor.id,
+        target: target.id,
+        attack,
+        attack_vector,
+        msg: target.name.clone(),
+    }
+}
+
+/// Moves towards the target but stops at the given distance
+fn advance_towards(
+    actor: &Actor,
+    target: &Actor,
+    keep_distance: usize,
+    cw: &CoreWorld,
+) -> Option<Action> {
+    let target_pos = MapPos::from_world_pos(target.pos);
+    let path = find_path_towards(actor, target, cw)?
+        .drain(..)
+        .take_while(|t| t.to_map_pos().distance(target_pos) >= keep_distance)
+        .collect();
+
+    let path = trim_path_to_move_distance(actor, path, cw);
+
+    if path.is_empty() {
+        None
+    } else {
+        Some(Action::MoveTo {
+            actor: actor.id,
+            path,
+        })
+    }
+}
+
+#[cfg(test)]
+fn with_test_world<F: Fn(&CoreWorld, TeamId, TeamId)>(f: F) {
+    use crate::core::flow::{
+        create_player_chars, create_team_cpu, create_team_player, init_combat_data,
+    };
+
+    let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+    let actors = create_player_chars(&generator);
+    let (team_player, team_cpu) = (create_team_player(), create_team_cpu());
+    let (player, cpu) = (team_player.id, team_cpu.id);
+    let data = init_combat_data(
+        actors,
+        vec![team_player, team_cpu],
+        generator,
+        TextureMap::default(),
+    );
+
+    f(&CoreWorld::new(&data.world), cpu, player);
+}
+
+#[cfg(test)]
+fn distance_after_move(action: &Action, enemy: &Actor) -> Option<usize> {
+    match action {
+        Action::MoveTo { path, .. } => path
+            .last()
+            .map(|t| t.to_map_pos().distance(MapPos::from_world_pos(enemy.pos))),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_skirmisher_backs_off_from_adjacent_enemies() {
+    with_test_world(|cw, team, enemy_team| {
+        let gunner = cw.generate_enemy(MapPos(4, 9), team, ActorTemplateName::new("actor#gunner"));
+        let zombi = cw.generate_enemy(
+            MapPos(4, 10),
+            enemy_team,
+            ActorTemplateName::new("enemy#zombi"),
+        );
+
+        assert!(matches!(gunner.behaviour, Some(AiBehaviour::Skirmisher)));
+
+        let action = skirmisher_action(&gunner, std::slice::from_ref(&zombi), cw);
+
+        assert!(distance_after_move(&action, &zombi).unwrap() > 1);
+    });
+}
+
+#[test]
+fn test_coward_flees_when_badly_hurt() {
+    with_test_world(|cw, team, enemy_team| {
+        let mut sucker =
+            cw.generate_enemy(MapPos(4, 9), team, ActorTemplateName::new("enemy#sucker"));
+        let zombi = cw.generate_enemy(
+            MapPos(4, 10),
+            enemy_team,
+            ActorTemplateName::new("enemy#zombi"),
+        );
+
+        let action = coward_action(&sucker, std::slice::from_ref(&zombi), cw);
+        assert!(matches!(action, Action::Attack { .. }));
+
+        sucker.health.remaining_wounds = 1;
+
+        let action = coward_action(&sucker, std::slice::from_ref(&zombi), cw);
+        assert!(distance_after_move(&action, &zombi).unwrap() > 1);
+    });
+}
+
+#[test]
+fn test_ambusher_only_creeps_up_while_underground() {
+    with_test_world(|cw, team, enemy_team| {
+        let prey = cw.generate_enemy(
+            MapPos(4, 6),
+            enemy_team,
+            ActorTemplateName::new("actor#tank"),
+        );
+        let worm = cw.generate_enemy(MapPos(4, 9), team, ActorTemplateName::new("enemy#worm"));
+        let zombi = cw.generate_enemy(MapPos(4, 9), team, ActorTemplateName::new("enemy#zombi"));
+
+        assert!(worm.is_underground());
+        assert!(!zombi.is_underground());
+
+        let action = ambusher_action(&worm, std::slice::from_ref(&prey), cw);
+        assert!(distance_after_move(&action, &prey).unwrap() < 3);
+
+        let action = ambusher_action(&zombi, std::slice::from_ref(&prey), cw);
+        assert!(matches!(action, Action::DoNothing(_)));
+    });
+}
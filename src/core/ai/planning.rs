@@ -0,0 +1,101 @@
+use crate::core::flow::TeamData;
+use crate::core::*;
+
+/// Bonus for cards which grant an additional activation (instead of just
+/// boosting an existing one)
+const BONUS_ACTIVATION: u8 = 5;
+
+/// Chooses the next card the AI wants to play during the planning phase and
+/// the team member to boost with it; the AI keeps its cards for those who are
+/// in the thick of the fight and only plays cards that actually help
+pub fn plan_boost(team: &TeamData, cw: &CoreWorld) -> Option<(ID, Card)> {
+    let enemies = cw
+        .actors()
+        .filter(|a| team.spotted.contains(&a.id) && a.is_concious())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut best: Option<(u32, ID, Card)> = None;
+
+    for a in cw.actors() {
+        if !team.team.is_member(a) || !a.is_concious() || !a.can_be_boosted() {
+            continue;
+        }
+
+        let threat = engagement(a, &enemies);
+        if threat == 0 {
+            continue;
+        }
+
+        for card in team.hand.iter() {
+            let score = threat * gain(a, *card) as u32;
+
+            if score > 0 && best.map(|(s, ..)| score > s).unwrap_or(true) {
+                best = Some((score, a.id, *card));
+            }
+        }
+    }
+
+    best.map(|(_, id, card)| (id, card))
+}
+
+/// The number of enemies the actor can fight (or be attacked by) this turn
+fn engagement(a: &Actor, enemies: &[Actor]) -> u32 {
+    let p = MapPos::from_world_pos(a.pos);
+
+    enemies
+        .iter()
+        .filter(|e| e.team != a.team)
+        .filter(|e| {
+            let d = p.distance(MapPos::from_world_pos(e.pos));
+            d <= reach(a) || d <= reach(e)
+        })
+        .count() as u32
+}
+
+fn reach(a: &Actor) -> usize {
+    let attack_distance = a.attacks().iter().map(|at| at.max_distance).max();
+    (a.move_distance() + attack_distance.unwrap_or(0)) as usize
+}
+
+/// How much the card would improve the attacks of the actor
+fn gain(a: &Actor, card: Card) -> u8 {
+    let value = |c: Card| {
+        a.attacks()
+            .iter()
+            .map(|at| c.value(at.to_hit.0))
+            .max()
+            .unwrap_or(0)
+    };
+
+    // see Actor::boost_activation
+    let boosted = a.activations.iter().find_map(|act| match act {
+        Activation::Single(c) => Some(*c),
+        Activation::Boosted(..) => None,
+    });
+
+    match boosted {
+        Some(current) => value(card).saturating_sub(value(current)),
+        None => value(card) + BONUS_ACTIVATION,
+    }
+}
+
+#[test]
+fn test_only_better_cards_are_worth_playing() {
+    use std::path::Path;
+
+    let generator = ObjectGenerator::new(Path::new("assets/data/"));
+    let tank = generator.generate_player(
+        WorldPos::new(0.0, 0.0, 0.0),
+        TeamId::new(0),
+        ActorTemplateName::new("actor#tank"),
+    );
+    let suite = tank.attacks()[0].to_hit.0;
+    let tank = tank.add_activation(Card::new(5, suite));
+
+    assert_eq!(gain(&tank, Card::new(3, suite)), 0);
+    assert_eq!(gain(&tank, Card::new(9, suite)), 4);
+
+    let tank = tank.boost_activation(Card::new(9, suite));
+    assert_eq!(gain(&tank, Card::new(3, suite)), 3 + BONUS_ACTIVATION);
+}
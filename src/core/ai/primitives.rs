@@ -1,4 +1,3 @@
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::num::NonZeroU8;
 
@@ -28,24 +27,49 @@ pub fn find_path_towards(actor: &Actor, target: &Actor, world: &CoreWorld) -> Op
 }
 
 fn movment_obstacles(actor: &Actor, world: &CoreWorld) -> ObstacleSet {
-    ObstacleSet(
-        world
-            .collect_obstacles()
-            .drain()
-            .filter_map(|(p, (oc, _))| movement_obstacle(actor, &oc).map(|o| (p, o)))
-            .collect::<HashMap<_, _>>(),
-    )
+    let mut obstacles = world
+        .map()
+        .tiles()
+        .filter_map(|t| terrain_obstacle(actor, t.tile_type()).map(|o| (t.to_map_pos(), o)))
+        .collect::<HashMap<_, _>>();
+
+    for (p, (oc, _)) in world.collect_obstacles().drain() {
+        if let Some(o) = movement_obstacle(actor, &oc) {
+            obstacles.insert(p, o);
+        }
+    }
+
+    ObstacleSet(obstacles)
+}
+
+/// Returns the beginning of the given path which the actor can travel within
+/// a single move
+pub fn trim_path_to_move_distance(actor: &Actor, path: Path, world: &CoreWorld) -> Path {
+    let obstacles = movment_obstacles(actor, world);
+    let budget = actor.move_distance() as f32;
+    let mut result = vec![];
+
+    for t in path {
+        result.push(t);
+
+        if obstacles.path_costs(&result) > budget {
+            result.pop();
+            break;
+        }
+    }
+
+    result
 }
 
 pub fn find_enemies(actor: &Actor, world: &CoreWorld) -> Vec<Actor> {
-    let team = &actor.team;
+    let teams = world.teams();
     let apos = MapPos::from_world_pos(actor.pos);
     let mut enemies = world
         .collect_obstacles()
         .drain()
         .filter_map(|(_, (_, id))| {
             if let Some(a) = id.and_then(|id| world.get_actor(id)) {
-                if &a.team != team {
+                if teams.is_hostile(a.team, actor.team) {
                     return Some(a.clone());
                 }
             }
@@ -53,16 +77,8 @@ pub fn find_enemies(actor: &Actor, world: &CoreWorld) -> Vec<Actor> {
         })
         .collect::<Vec<_>>();
 
-    enemies.sort_by(|a1, a2| {
-        let d1 = apos.distance(MapPos::from_world_pos(a1.pos));
-        let d2 = apos.distance(MapPos::from_world_pos(a2.pos));
-
-        if d1 <= d2 {
-            Ordering::Less
-        } else {
-            Ordering::Greater
-        }
-    });
+    // (the ID breaks ties so every peer of a networked game sees the same order)
+    enemies.sort_by_key(|a| (apos.distance(MapPos::from_world_pos(a.pos)), a.id));
 
     enemies
 }
@@ -75,10 +91,59 @@ pub fn possible_attacks(
     actor
         .attacks()
         .drain(..)
-        .filter_map(|attack| attack_vector(actor, target, &attack, world).map(|av| (attack, av)))
+        .filter_map(|attack| {
+            attack_vector(actor, target.pos, &attack, world).map(|av| (attack, av))
+        })
         .collect()
 }
 
+/// Returns how the terrain hinders the given actor from moving
+pub fn terrain_obstacle(a: &Actor, tt: TileType) -> Option<Obstacle> {
+    let difficult = Obstacle::Impediment(NonZeroU8::new(2).unwrap(), 0);
+    let dangerous = Obstacle::Impediment(NonZeroU8::new(5).unwrap(), 0);
+
+    match tt {
+        TileType::Floor | TileType::HighGround => None,
+
+        TileType::Void => Some(Obstacle::Blocker),
+
+        TileType::Rough => {
+            if a.is_flying() || a.is_underground() {
+                None
+            } else {
+                Some(difficult)
+            }
+        }
+
+        TileType::Water => {
+            if a.is_underground() {
+                Some(Obstacle::Blocker)
+            } else if a.is_flying() {
+                None
+            } else {
+                Some(difficult)
+            }
+        }
+
+        TileType::Pit => {
+            if a.is_flying() {
+                None
+            } else {
+                Some(Obstacle::Blocker)
+            }
+        }
+
+        TileType::Hazard => {
+            if a.is_flying() || a.is_underground() {
+                None
+            } else {
+                // no one would step onto this tile willingly
+                Some(dangerous)
+            }
+        }
+    }
+}
+
 fn movement_obstacle(a: &Actor, oc: &ObstacleCmp) -> Option<Obstacle> {
     if a.is_flying() {
         return oc.movement.1;
@@ -93,15 +158,21 @@ fn movement_obstacle(a: &Actor, oc: &ObstacleCmp) -> Option<Obstacle> {
 
 pub fn attack_vector(
     attacker: &Actor,
-    target: &Actor,
+    target_pos: WorldPos,
     attack: &AttackOption,
     world: &CoreWorld,
 ) -> Option<AttackVector> {
     let from = MapPos::from_world_pos(attacker.pos);
-    let to = MapPos::from_world_pos(target.pos);
+    let to = MapPos::from_world_pos(target_pos);
     let max_distance = attacker.move_distance() + attack.max_distance;
     let d = from.distance(to);
 
+    if from == to {
+        // e.g. standing on top of a crumbled wall
+        // => there is no line of attack
+        return None;
+    }
+
     if d > max_distance.into() {
         // target is out of reach => no need to check for obstacles
         return None;
@@ -112,11 +183,16 @@ pub fn attack_vector(
         return None;
     }
 
+    let advance_distance = d.checked_sub(attack.max_distance.into()).unwrap_or(0);
+    if advance_distance > 0 && find_charge_path(attacker, target_pos, world).is_none() {
+        // the attacker would have to charge but there is no way to the target
+        return None;
+    }
+
     let obstacles = world.collect_obstacles();
     let line_of_attack = SuperLineIter::new(from, to);
     let mut result: AttackVector = vec![];
     let mut is_advancing = true;
-    let advance_distance = d.checked_sub(attack.max_distance.into()).unwrap_or(0);
     let mut cover = Cover::none();
 
     for pos in line_of_attack {
@@ -135,6 +211,15 @@ pub fn attack_vector(
 
         is_advancing = is_advancing && !is_target && from.distance(pos) <= advance_distance;
 
+        if is_advancing {
+            let tile_type = world.map().get_tile(pos).unwrap().tile_type();
+            if let Some(Obstacle::Blocker) = terrain_obstacle(attacker, tile_type) {
+                // there is no way to charge across this tile
+                // => no attack possible
+                return None;
+            }
+        }
+
         if let Some((obs, id)) = obstacles.get(&pos) {
             if is_advancing {
                 // we are still advancing
@@ -152,6 +237,11 @@ pub fn attack_vector(
                 )
             }) {
                 match obs {
+                    Obstacle::Blocker if is_target => {
+                        // the obstacle itself is what we want to attack
+                        result.push((pos, is_target, cover.clone(), *id));
+                    }
+
                     Obstacle::Blocker => {
                         // cannot reach target; obstacle blocks the way completely
                         // => no attack possible
@@ -188,11 +278,11 @@ pub fn attack_vector(
 
 pub fn find_charge_path(
     moving_actor: &Actor,
-    target_actor: &Actor,
+    target_pos: WorldPos,
     world: &CoreWorld,
 ) -> Option<Path> {
     let from_pos = MapPos::from_world_pos(moving_actor.pos);
-    let target_pos = MapPos::from_world_pos(target_actor.pos);
+    let target_pos = MapPos::from_world_pos(target_pos);
     if from_pos == target_pos {
         return None;
     }
@@ -217,6 +307,12 @@ pub fn find_charge_path(
             return None;
         }
 
+        if tile_pos != target_pos {
+            if let Some(Obstacle::Blocker) = terrain_obstacle(moving_actor, t.tile_type()) {
+                return None;
+            }
+        }
+
         result.push(t);
 
         if tile_pos == target_pos {
@@ -236,32 +332,70 @@ pub fn add_option<P: Into<MapPos>>(p: P, a: Action, result: &mut PlayerActionOpt
     }
 }
 
-pub fn add_move_to_options(active_actor: &Actor, w: &CoreWorld, result: &mut PlayerActionOptions) {
-    let p0 = MapPos::from_world_pos(active_actor.pos);
+/// Returns every tile the actor can reach with a single move (together with
+/// the path to get there)
+pub fn reachable_tiles(actor: &Actor, w: &CoreWorld) -> Vec<(Tile, Path)> {
+    let p0 = MapPos::from_world_pos(actor.pos);
     let t0 = w.map().get_tile(p0).unwrap();
-    let d = NonZeroU8::new(active_actor.move_distance()).unwrap();
-    let obstacles = movment_obstacles(active_actor, w);
+    let d = NonZeroU8::new(actor.move_distance()).unwrap();
+    let obstacles = movment_obstacles(actor, w);
+    let mut result = vec![];
 
     for t in w.map().neighbors(t0, d, &obstacles) {
         if let Some(path) = w.map().find_path(p0, t.to_map_pos(), &obstacles) {
-            if path.len() <= d.get() as usize {
-                let action = Action::MoveTo {
-                    actor: active_actor.id,
-                    path,
-                };
-                add_option(t, action, result);
+            if obstacles.path_costs(&path) <= d.get() as f32 {
+                result.push((t, path));
             }
         }
     }
+
+    result
+}
+
+/// Returns the path to the reachable tile which is the furthest away from
+/// the given enemies (if there is any better place than the current one)
+pub fn find_retreat_path(actor: &Actor, enemies: &[Actor], w: &CoreWorld) -> Option<Path> {
+    let distance_to_enemies = |p: MapPos| {
+        enemies
+            .iter()
+            .map(|e| p.distance(MapPos::from_world_pos(e.pos)))
+            .min()
+            .unwrap_or(usize::MAX)
+    };
+
+    let current_distance = distance_to_enemies(MapPos::from_world_pos(actor.pos));
+
+    reachable_tiles(actor, w)
+        .drain(..)
+        .map(|(t, path)| (distance_to_enemies(t.to_map_pos()), path))
+        .filter(|(d, _)| *d > current_distance)
+        .max_by_key(|(d, path)| (*d, usize::MAX - path.len()))
+        .map(|(_, path)| path)
+}
+
+pub fn add_move_to_options(active_actor: &Actor, w: &CoreWorld, result: &mut PlayerActionOptions) {
+    for (t, path) in reachable_tiles(active_actor, w) {
+        let action = Action::MoveTo {
+            actor: active_actor.id,
+            path,
+        };
+        add_option(t, action, result);
+    }
 }
 
 pub fn add_combat_options(active_actor: &Actor, w: &CoreWorld, result: &mut PlayerActionOptions) {
     let attacks = active_actor.attacks();
+    let vision = team_vision(active_actor.team, w);
+    let teams = w.teams();
 
     for other in w.actors() {
-        if other.id != active_actor.id && other.team != active_actor.team {
+        // no friendly fire
+        if other.id != active_actor.id
+            && teams.relation(other.team, active_actor.team) != Relation::Allied
+            && vision.can_see_actor(other)
+        {
             for a in attacks.iter() {
-                if let Some(attack_vector) = attack_vector(active_actor, other, a, w) {
+                if let Some(attack_vector) = attack_vector(active_actor, other.pos, a, w) {
                     if !attack_vector.is_empty() {
                         let msg = format!("{} at {}", a.name, other.name);
                         let action = Action::Attack {
@@ -279,6 +413,29 @@ pub fn add_combat_options(active_actor: &Actor, w: &CoreWorld, result: &mut Play
         }
     }
 
+    for s in w.structures() {
+        if s.is_destroyed() {
+            continue;
+        }
+
+        for a in attacks.iter() {
+            if let Some(attack_vector) = attack_vector(active_actor, s.pos, a, w) {
+                if !attack_vector.is_empty() {
+                    let msg = format!("{} at {}", a.name, s.name);
+                    let action = Action::Attack {
+                        attacker: active_actor.id,
+                        target: s.id,
+                        attack: a.clone(),
+                        attack_vector,
+                        msg,
+                    };
+
+                    add_option(s.pos, action, result);
+                }
+            }
+        }
+    }
+
     add_option(
         active_actor.pos,
         Action::AddTrait {
@@ -290,6 +447,32 @@ pub fn add_combat_options(active_actor: &Actor, w: &CoreWorld, result: &mut Play
     );
 }
 
+pub fn add_aid_options(active_actor: &Actor, w: &CoreWorld, result: &mut PlayerActionOptions) {
+    let aids = active_actor.aids();
+    let from = MapPos::from_world_pos(active_actor.pos);
+    let teams = w.teams();
+
+    for other in w.actors() {
+        if teams.relation(other.team, active_actor.team) != Relation::Allied {
+            continue;
+        }
+
+        let d = from.distance(MapPos::from_world_pos(other.pos));
+
+        for aid in aids.iter() {
+            if d <= aid.max_distance.into() && other.can_be_aided_with(aid) {
+                let action = Action::Aid {
+                    actor: active_actor.id,
+                    target: other.id,
+                    aid: aid.clone(),
+                };
+
+                add_option(other.pos, action, result);
+            }
+        }
+    }
+}
+
 // pub fn add_noop_option(active_actor: &Actor, result: &mut PlayerActionOptions) {
 //     add_option(
 //         active_actor.pos,
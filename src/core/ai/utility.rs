@@ -0,0 +1,284 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use rand::prelude::*;
+
+use crate::components::ObstacleCmp;
+use crate::core::*;
+
+use super::{possible_player_actions, PlayerActionOptions};
+
+/// How much the AI values its own health compared to hurting the enemy
+const CAUTION: f32 = 0.5;
+
+/// How much the AI values getting closer to the enemy
+const AGGRESSION: f32 = 0.1;
+
+/// Scores every action the actor could perform (the same options a player
+/// would have) and picks one of the best
+pub fn utility_action(actor: &Actor, enemies: &[Actor], cw: &CoreWorld) -> Action {
+    let temperature = match actor.behaviour {
+        Some(AiBehaviour::Tactician { temperature }) => temperature,
+        _ => 0.0,
+    };
+
+    let obstacles = cw.collect_obstacles();
+    let mut options = possible_player_actions(actor, cw)
+        .drain()
+        .collect::<Vec<_>>();
+
+    // the order of a HashMap differs from machine to machine but every peer of
+    // a networked game has to make the same choice
+    options.sort_by_key(|(p, _)| (p.0, p.1));
+
+    let options = options
+        .drain(..)
+        .flat_map(|(_, actions)| actions)
+        .map(|action| {
+            let score = score_action(&action, actor, enemies, &obstacles, cw);
+            (action, score)
+        })
+        .collect::<Vec<_>>();
+
+    choose(options, temperature).unwrap_or(Action::DoNothing(actor.id))
+}
+
+/// Returns where to find the best of the given options (e.g. to advise the
+/// player what to do)
+pub fn best_option(
+    actor: &Actor,
+    enemies: &[Actor],
+    options: &PlayerActionOptions,
+    cw: &CoreWorld,
+) -> Option<(MapPos, usize)> {
+    let obstacles = cw.collect_obstacles();
+    let mut positions = options.keys().copied().collect::<Vec<_>>();
+    positions.sort_by_key(|p| (p.0, p.1));
+
+    positions
+        .iter()
+        .map(|p| (*p, options.get(p).unwrap()))
+        .flat_map(|(p, actions)| actions.iter().enumerate().map(move |(i, a)| (p, i, a)))
+        .map(|(p, i, a)| (p, i, score_action(a, actor, enemies, &obstacles, cw)))
+        .max_by(|(.., s1), (.., s2)| s1.partial_cmp(s2).unwrap_or(Ordering::Equal))
+        .map(|(p, i, _)| (p, i))
+}
+
+fn score_action(
+    action: &Action,
+    actor: &Actor,
+    enemies: &[Actor],
+    obstacles: &HashMap<MapPos, (ObstacleCmp, Option<ID>)>,
+    cw: &CoreWorld,
+) -> f32 {
+    let here = MapPos::from_world_pos(actor.pos);
+    let danger_here = CAUTION * threat(actor, here, enemies, obstacles);
+
+    match action {
+        Action::Attack { target, attack, .. } => {
+            match enemies.iter().find(|e| e.id == *target) {
+                Some(t) => {
+                    let wounds = expected_wounds(actor, attack, t, cw);
+                    let kill_bonus = if wounds >= t.health.remaining_wounds as f32 {
+                        1.0
+                    } else {
+                        0.0
+                    };
+
+                    // finish off those who are already hurt
+                    let focus_fire = 0.1 * (t.health.recieved_wounds + t.health.pain) as f32;
+
+                    wounds + kill_bonus + focus_fire - danger_here
+                }
+
+                // do not waste time with walls or with enemies we do not know of
+                None => -danger_here,
+            }
+        }
+
+        Action::MoveTo { path, .. } => {
+            let to = match path.last() {
+                Some(t) => t.to_map_pos(),
+                None => return -danger_here,
+            };
+
+            let progress = closest_distance(here, enemies) - closest_distance(to, enemies);
+
+            AGGRESSION * progress - CAUTION * threat(actor, to, enemies, obstacles)
+        }
+
+        Action::Aid { target, .. } => match cw.get_actor(*target) {
+            Some(t) if !t.is_concious() => 2.0 - danger_here,
+            Some(t) => 0.5 * t.health.recieved_wounds as f32 - danger_here,
+            None => -danger_here,
+        },
+
+        // e.g. blocking
+        Action::AddTrait { .. } => -0.5 * danger_here,
+
+        _ => -danger_here,
+    }
+}
+
+/// The number of wounds the attack is expected to cause (based on the odds
+/// to hit and to pierce the armor of the target with the decks of both teams)
+fn expected_wounds(attacker: &Actor, attack: &AttackOption, target: &Actor, cw: &CoreWorld) -> f32 {
+    let teams = cw.teams();
+    let attacker_cards = teams.get(&attacker.team).deck.all_cards();
+    let target_cards = teams.get(&target.team).deck.all_cards();
+    let effort = attacker
+        .active_activation
+        .as_ref()
+        .map(|a| a.value_card(attack.to_hit.0).value(attack.to_hit.0))
+        .unwrap_or(0);
+
+    let chance_to_hit = 1.0
+        - success_chance(
+            &Challenge {
+                advantage: 0,
+                challenge_type: attack.defence,
+                skill_val: target.skill(Suite::PhysicalAg, 0),
+                target_num: attacker.skill(attack.to_hit.0, attack.to_hit.1) + effort,
+            },
+            target_cards,
+        );
+
+    let wounds: f32 = challenge_odds(
+        &Challenge {
+            advantage: 0,
+            challenge_type: attack.to_wound.0,
+            skill_val: attacker.skill(attack.to_wound.0, attack.to_wound.1),
+            target_num: 3.max(target.soak().saturating_sub(attack.rend)),
+        },
+        attacker_cards,
+    )
+    .iter()
+    .map(|(lvl, p)| (*lvl).max(0) as f32 * p)
+    .sum();
+
+    chance_to_hit * wounds
+}
+
+/// Estimates how many enemies could attack the actor at the given position
+/// (reduced by any cover there)
+fn threat(
+    actor: &Actor,
+    p: MapPos,
+    enemies: &[Actor],
+    obstacles: &HashMap<MapPos, (ObstacleCmp, Option<ID>)>,
+) -> f32 {
+    enemies
+        .iter()
+        .filter(|e| e.is_concious())
+        .map(|e| {
+            let from = MapPos::from_world_pos(e.pos);
+            let reach = e.move_distance() as usize
+                + e.attacks()
+                    .iter()
+                    .map(|a| a.max_distance as usize)
+                    .max()
+                    .unwrap_or(0);
+
+            if from.distance(p) > reach {
+                0.0
+            } else {
+                1.0 - cover(from, p, actor.id, obstacles)
+            }
+        })
+        .sum()
+}
+
+/// Returns how well the target position is covered against attacks from the
+/// given position (0.0 = not at all, 1.0 = completely)
+fn cover(
+    from: MapPos,
+    to: MapPos,
+    ignore: ID,
+    obstacles: &HashMap<MapPos, (ObstacleCmp, Option<ID>)>,
+) -> f32 {
+    if from == to {
+        return 0.0;
+    }
+
+    let p0 = from.to_world_pos().as_xy();
+    let p1 = to.to_world_pos().as_xy();
+    let mut result: f32 = 0.0;
+
+    for p in SuperLineIter::new(from, to).take_while(|p| *p != to) {
+        if p == from || from.distance(p) <= 1 {
+            // obstacles right next to the attacker do not count
+            // (see attack_vector)
+            continue;
+        }
+
+        let obstacle = obstacles
+            .get(&p)
+            .filter(|(_, id)| *id != Some(ignore))
+            .and_then(|(obs, _)| obs.reach.as_ref())
+            .and_then(|hitbox| hitbox.obstacle_at(p.to_world_pos().as_xy(), p0, p1));
+
+        result = result.max(match obstacle {
+            Some(Obstacle::Blocker) => 1.0,
+            Some(Obstacle::Impediment(o, _)) => o.get() as f32 / 100.0,
+            None => 0.0,
+        });
+    }
+
+    result.min(1.0)
+}
+
+fn closest_distance(p: MapPos, enemies: &[Actor]) -> f32 {
+    enemies
+        .iter()
+        .map(|e| p.distance(MapPos::from_world_pos(e.pos)))
+        .min()
+        .unwrap_or(0) as f32
+}
+
+/// Picks one of the options based on their scores; the higher the
+/// temperature, the more likely it is to pick a worse option (a temperature
+/// of 0 always picks the best one)
+fn choose(mut options: Vec<(Action, f32)>, temperature: f32) -> Option<Action> {
+    if options.is_empty() {
+        return None;
+    }
+
+    let best = options
+        .iter()
+        .map(|(_, s)| *s)
+        .fold(f32::NEG_INFINITY, f32::max);
+
+    if temperature <= 0.0 {
+        let idx = options.iter().position(|(_, s)| *s >= best).unwrap();
+        return Some(options.remove(idx).0);
+    }
+
+    // softmax (shifted by the best score to avoid overflows)
+    let weights = options
+        .iter()
+        .map(|(_, s)| ((s - best) / temperature).exp())
+        .collect::<Vec<_>>();
+
+    let mut roll = with_rng(|rng| rng.gen::<f32>()) * weights.iter().sum::<f32>();
+
+    for (idx, w) in weights.iter().enumerate() {
+        if roll < *w {
+            return Some(options.remove(idx).0);
+        }
+        roll -= w;
+    }
+
+    options.pop().map(|(a, _)| a)
+}
+
+#[test]
+fn test_cold_choice_picks_the_best_option() {
+    let id = ID::new();
+    let options = vec![
+        (Action::DoNothing(id), 0.0),
+        (Action::StartTurn(id), 2.0),
+        (Action::ActivateActor(id), 1.0),
+    ];
+
+    assert!(matches!(choose(options, 0.0), Some(Action::StartTurn(_))));
+}
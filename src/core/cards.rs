@@ -1,10 +1,14 @@
 extern crate rand;
 
 use rand::prelude::*;
-use serde::Deserialize;
+use ron::de::from_reader;
+use serde::{Deserialize, Serialize};
 use std::cmp::max;
+use std::{collections::HashMap, fs::File, iter::FromIterator, path::Path};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+use crate::core::with_rng;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Suite {
     PhysicalStr,
     PhysicalAg,
@@ -60,7 +64,7 @@ pub enum SuiteMatch {
     No,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Card {
     pub value: u8,
     pub suite: Suite,
@@ -82,10 +86,43 @@ impl Card {
     }
 }
 
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.value == 0 {
+            return write!(f, "Cursed");
+        }
+
+        let value = match self.value {
+            1 => "A".to_string(),
+            11 => "J".to_string(),
+            12 => "Q".to_string(),
+            13 => "K".to_string(),
+            _ => format!("{}", self.value),
+        };
+
+        let suite = match self.suite {
+            Suite::PhysicalStr => "Clubs",
+            Suite::PhysicalAg => "Spades",
+            Suite::MentalStr => "Hearts",
+            Suite::MentalAg => "Diamonds",
+            Suite::Physical => "Black",
+            Suite::Mental => "Red",
+            Suite::Any => return write!(f, "Wild {}", value),
+            _ => panic!("Non valid suite: {:?}", self.suite),
+        };
+
+        write!(f, "{} of {}", value, suite)
+    }
+}
+
 #[derive(Clone)]
 pub struct Deck {
+    /// Every card which belongs to the deck (dealt or not)
+    all_cards: Vec<Card>,
+    /// The cards which are left until the deck is shuffled again (the next
+    /// card is the last one)
     cards: Vec<Card>,
-    shuffle: &'static (dyn Fn() -> Vec<Card> + Send + Sync),
+    shuffle: fn(&[Card]) -> Vec<Card>,
 }
 
 use std::fmt;
@@ -96,23 +133,38 @@ impl fmt::Debug for Deck {
 }
 
 impl Deck {
-    pub fn new<F>(shuffle: &'static F) -> Self
-    where
-        F: Fn() -> Vec<Card> + Sync + Send,
-    {
+    /// Creates a deck of the given cards (which are shuffled before they are
+    /// dealt)
+    pub fn new(all_cards: Vec<Card>) -> Self {
+        if all_cards.is_empty() {
+            panic!("A deck needs at least one card");
+        }
+
         Self {
+            all_cards,
             cards: vec![],
-            shuffle,
+            shuffle: Self::rnd_shuffle,
         }
     }
 
+    /// Creates a deck which deals the given cards always in the same order
+    /// (for tests)
+    #[allow(dead_code)]
+    pub fn unshuffled(all_cards: Vec<Card>) -> Self {
+        Self {
+            shuffle: |cards| cards.to_vec(),
+            ..Self::new(all_cards)
+        }
+    }
+
+    #[cfg(test)]
     pub fn new_rnd() -> Self {
-        Self::new(&Self::rnd_shuffle)
+        Self::new(standard_cards())
     }
 
     pub fn deal(&mut self) -> Card {
         if self.cards.is_empty() {
-            let mut cards: Vec<Card> = (*(self.shuffle)()).to_vec();
+            let mut cards = (self.shuffle)(&self.all_cards);
             cards.reverse();
             self.cards = cards;
         }
@@ -120,27 +172,176 @@ impl Deck {
         self.cards.pop().unwrap() // unwrapping is safe because the deck is shuffelled when empty
     }
 
-    fn rnd_shuffle() -> Vec<Card> {
-        let suites = vec![
-            Suite::PhysicalStr,
-            Suite::PhysicalAg,
-            Suite::MentalStr,
-            Suite::MentalAg,
-        ];
-        let mut cards = Vec::new();
-
-        for suite in suites {
-            for value in 1..=13 {
-                cards.push(Card { value, suite })
+    /// Every card which belongs to the deck (no matter if it has been dealt)
+    pub fn all_cards(&self) -> &[Card] {
+        &self.all_cards
+    }
+
+    /// The number of cards which can be dealt until the deck is shuffled
+    pub fn num_cards_left(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Adds cards to (or removes cards from) the deck; the deck is shuffled
+    /// anew afterwards (fails if no card would be left)
+    pub fn change(mut self, c: &DeckChange) -> Result<Self, String> {
+        match c {
+            DeckChange::Add(cs) => self.all_cards.extend(cs.cards()),
+            DeckChange::Remove(cs) => {
+                for card in cs.cards() {
+                    if let Some(idx) = self.all_cards.iter().position(|c| *c == card) {
+                        self.all_cards.remove(idx);
+                    }
+                }
             }
         }
 
-        let mut rng = thread_rng();
-        cards.shuffle(&mut rng);
+        if self.all_cards.is_empty() {
+            return Err(format!("Cannot remove every card of a deck ({:?})", c));
+        }
+
+        self.cards.clear();
+        Ok(self)
+    }
+
+    fn rnd_shuffle(all_cards: &[Card]) -> Vec<Card> {
+        let mut cards = all_cards.to_vec();
+
+        with_rng(|rng| cards.shuffle(rng));
         cards
     }
 }
 
+/// The suites a card can have (the others only describe challenges)
+const CARD_SUITES: [Suite; 4] = [
+    Suite::PhysicalStr,
+    Suite::PhysicalAg,
+    Suite::MentalStr,
+    Suite::MentalAg,
+];
+
+/// The classic 52 cards (4 suites from ace to king)
+#[cfg(test)]
+fn standard_cards() -> Vec<Card> {
+    CardSet::Suites(CARD_SUITES.to_vec(), 1, 13).cards()
+}
+
+/// A number of cards (as described in the data files)
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub enum CardSet {
+    /// Every value of the given range (inclusive) in each of the given suites
+    Suites(Vec<Suite>, u8, u8),
+    /// A card of the given value which fits every challenge
+    Wild(u8),
+    /// A card which is worth nothing at all
+    Cursed,
+}
+
+impl CardSet {
+    pub fn cards(&self) -> Vec<Card> {
+        match self {
+            CardSet::Suites(suites, from, to) => suites
+                .iter()
+                .flat_map(|suite| (*from..=*to).map(move |value| Card::new(value, *suite)))
+                .collect(),
+
+            CardSet::Wild(value) => vec![Card::new(*value, Suite::Any)],
+
+            CardSet::Cursed => vec![Card::new(0, Suite::Any)],
+        }
+    }
+
+    /// Checks that the set only contains cards which can actually be dealt
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            CardSet::Suites(suites, from, to) => {
+                if let Some(s) = suites.iter().find(|s| !CARD_SUITES.contains(s)) {
+                    return Err(format!("{:?} is not the suite of a card", s));
+                }
+
+                if suites.is_empty() || *from < 1 || *to > 13 || from > to {
+                    return Err(format!("No cards in {:?}", self));
+                }
+
+                Ok(())
+            }
+
+            CardSet::Wild(value) if *value < 1 || *value > 13 => {
+                Err(format!("Invalid value of a wild card: {}", value))
+            }
+
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Cards which are added to or removed from a deck (e.g. by a trait)
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub enum DeckChange {
+    Add(CardSet),
+    Remove(CardSet),
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct DeckName(String);
+
+impl DeckName {
+    pub fn new(n: impl ToString) -> Self {
+        Self(n.to_string())
+    }
+}
+
+/// The decks which are described in the data files
+#[derive(Default)]
+pub struct DeckStorage {
+    decks: HashMap<String, Vec<CardSet>>,
+}
+
+impl DeckStorage {
+    pub fn new(path: &Path) -> Self {
+        let p = path.join("decks.ron");
+        let f = match File::open(p) {
+            Ok(result) => result,
+            Err(e) => {
+                panic!("Error opening deck config file: {:?}", e);
+            }
+        };
+
+        let decks: Vec<(String, Vec<CardSet>)> = match from_reader(f) {
+            Ok(result) => result,
+            Err(e) => {
+                panic!("Error parsing deck config: {:?}", e);
+            }
+        };
+
+        for (name, card_sets) in decks.iter() {
+            if card_sets.is_empty() {
+                panic!("Invalid deck {}: it has no cards", name);
+            }
+
+            for card_set in card_sets.iter() {
+                if let Err(e) = card_set.validate() {
+                    panic!("Invalid deck {}: {}", name, e);
+                }
+            }
+        }
+
+        Self {
+            decks: HashMap::from_iter(decks),
+        }
+    }
+
+    pub fn get(&self, deck_name: &DeckName) -> Deck {
+        let DeckName(key) = deck_name;
+        let card_sets = match self.decks.get(key) {
+            Some(card_sets) => card_sets,
+            None => panic!("Unknown deck: {}", key),
+        };
+
+        Deck::new(card_sets.iter().flat_map(CardSet::cards).collect())
+    }
+}
+
 #[allow(dead_code)]
 fn fixed_deck() -> Vec<Card> {
     use Suite::*;
@@ -156,8 +357,7 @@ fn fixed_deck() -> Vec<Card> {
 fn test_allow_deterministic_cards() {
     use Suite::*;
 
-    // let mut deck = Deck::new(|| vec![Card::spades(1), Card::hearts(2)]);
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
 
     // draw all cards from deck
     // should be in the same order we put them in
@@ -174,7 +374,51 @@ fn test_allow_deterministic_cards() {
     assert_eq!(deck.deal(), Card::new(7, MentalAg));
 }
 
-#[derive(Debug)]
+#[test]
+fn test_traits_can_change_the_deck() {
+    use Suite::*;
+
+    let ten_of_clubs = CardSet::Suites(vec![PhysicalStr], 10, 10);
+    let deck = Deck::unshuffled(fixed_deck())
+        .change(&DeckChange::Add(CardSet::Wild(10)))
+        .and_then(|d| d.change(&DeckChange::Remove(ten_of_clubs)))
+        .unwrap();
+
+    assert_eq!(
+        deck.all_cards(),
+        &[
+            Card::new(9, PhysicalAg),
+            Card::new(8, MentalStr),
+            Card::new(7, MentalAg),
+            Card::new(10, Any),
+        ]
+    );
+
+    // (the deck must not run out of cards)
+    let others = CardSet::Suites(vec![PhysicalAg, MentalStr, MentalAg], 1, 13);
+    let deck = deck.change(&DeckChange::Remove(others)).unwrap();
+    assert!(deck.change(&DeckChange::Remove(CardSet::Wild(10))).is_err());
+}
+
+#[test]
+fn test_only_playable_cards_are_valid() {
+    use Suite::*;
+
+    let is_valid = |set: CardSet| set.validate().is_ok();
+
+    assert!(is_valid(CardSet::Suites(vec![MentalAg], 1, 13)));
+    assert!(is_valid(CardSet::Wild(13)));
+    assert!(is_valid(CardSet::Cursed));
+
+    assert!(!is_valid(CardSet::Suites(vec![Physical], 1, 13)));
+    assert!(!is_valid(CardSet::Suites(vec![Any], 1, 13)));
+    assert!(!is_valid(CardSet::Suites(vec![PhysicalStr], 0, 13)));
+    assert!(!is_valid(CardSet::Suites(vec![PhysicalStr], 1, 14)));
+    assert!(!is_valid(CardSet::Suites(vec![], 1, 13)));
+    assert!(!is_valid(CardSet::Wild(0)));
+}
+
+#[derive(Debug, Clone)]
 pub struct Challenge {
     pub advantage: i8,
     pub challenge_type: Suite,
@@ -190,21 +434,68 @@ pub struct ChallengeResult {
 
 pub fn resolve_challenge(c: Challenge, deck: &mut Deck) -> ChallengeResult {
     let draw = draw(deck, c.advantage, c.challenge_type);
-    let val = c.skill_val + &draw.0.value(c.challenge_type);
-    let success_lvl = if val >= c.target_num {
+    let success_lvl = success_lvl(&c, &draw.0);
+
+    ChallengeResult { draw, success_lvl }
+}
+
+fn success_lvl(c: &Challenge, card: &Card) -> i8 {
+    let val = c.skill_val + card.value(c.challenge_type);
+
+    if val >= c.target_num {
         (val / max(1, c.target_num)) as i8
     } else {
         -1 * (c.target_num / max(1, val)) as i8
-    };
+    }
+}
 
-    ChallengeResult { draw, success_lvl }
+/// Returns the probabilities of every possible success level of a challenge
+/// (assuming a freshly shuffled deck of the given cards)
+pub fn challenge_odds(c: &Challenge, cards: &[Card]) -> Vec<(i8, f32)> {
+    let mut cards = cards.to_vec();
+    let draws = c.advantage.unsigned_abs() as i32 + 1;
+
+    // sort cards from worst to best so that the chance to draw a card which
+    // is at least as good as the current one can be accumulated
+    cards.sort_by_key(|card| success_lvl(c, card));
+
+    let n = cards.len() as f32;
+    let mut result: Vec<(i8, f32)> = vec![];
+
+    for (i, card) in cards.iter().enumerate() {
+        let lvl = success_lvl(c, card);
+        let (below, upto) = (i as f32 / n, (i + 1) as f32 / n);
+        let p = if c.advantage >= 0 {
+            // the best of all drawn cards counts
+            upto.powi(draws) - below.powi(draws)
+        } else {
+            // the worst of all drawn cards counts
+            (1.0 - below).powi(draws) - (1.0 - upto).powi(draws)
+        };
+
+        match result.last_mut() {
+            Some((l, acc)) if *l == lvl => *acc += p,
+            _ => result.push((lvl, p)),
+        }
+    }
+
+    result
+}
+
+/// Returns the chance to succeed at the given challenge
+pub fn success_chance(c: &Challenge, cards: &[Card]) -> f32 {
+    challenge_odds(c, cards)
+        .iter()
+        .filter(|(lvl, _)| *lvl > 0)
+        .map(|(_, p)| p)
+        .sum()
 }
 
 #[test]
 fn test_can_resolve_simple_challenge() {
     use Suite::*;
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let challenge = Challenge {
         advantage: 0,
         challenge_type: Suite::PhysicalAg,
@@ -220,7 +511,7 @@ fn test_can_resolve_simple_challenge() {
     );
     assert_eq!(result.success_lvl, 1); // 5 (skill) + 5 (half value for 10oC) VS 10 (TN)
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let challenge = Challenge {
         advantage: 0,
         challenge_type: Suite::MentalAg,
@@ -233,6 +524,51 @@ fn test_can_resolve_simple_challenge() {
     assert_eq!(result.success_lvl, -2); // 5 (skill) + 0 (zero for 10oC) VS 10
 }
 
+#[test]
+fn test_advantage_improves_the_odds() {
+    let challenge = |advantage| Challenge {
+        advantage,
+        challenge_type: Suite::PhysicalAg,
+        skill_val: 3,
+        target_num: 10,
+    };
+
+    let cards = standard_cards();
+    let total: f32 = challenge_odds(&challenge(0), &cards)
+        .iter()
+        .map(|(_, p)| p)
+        .sum();
+    assert!((total - 1.0).abs() < 0.001);
+
+    let p = success_chance(&challenge(0), &cards);
+    assert!(success_chance(&challenge(1), &cards) > p);
+    assert!(success_chance(&challenge(-1), &cards) < p);
+}
+
+#[test]
+fn test_odds_depend_on_the_deck() {
+    let challenge = Challenge {
+        advantage: 0,
+        challenge_type: Suite::PhysicalAg,
+        skill_val: 3,
+        target_num: 10,
+    };
+
+    let standard = standard_cards();
+    let lucky = Deck::new(standard_cards())
+        .change(&DeckChange::Add(CardSet::Wild(13)))
+        .and_then(|d| d.change(&DeckChange::Add(CardSet::Wild(13))))
+        .unwrap();
+    let cursed = Deck::new(standard_cards())
+        .change(&DeckChange::Add(CardSet::Cursed))
+        .and_then(|d| d.change(&DeckChange::Add(CardSet::Cursed)))
+        .unwrap();
+
+    let p = success_chance(&challenge, &standard);
+    assert!(success_chance(&challenge, lucky.all_cards()) > p);
+    assert!(success_chance(&challenge, cursed.all_cards()) < p);
+}
+
 fn draw(deck: &mut Deck, advantage: i8, s: Suite) -> (Card, Vec<Card>) {
     if advantage == 0 {
         let card = deck.deal();
@@ -254,7 +590,7 @@ fn draw(deck: &mut Deck, advantage: i8, s: Suite) -> (Card, Vec<Card>) {
 fn test_draw_without_advantage() {
     use Suite::*;
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, 0, Suite::PhysicalAg);
     assert_eq!(c, Card::new(10, PhysicalStr));
     assert_eq!(d, vec![Card::new(10, PhysicalStr)]);
@@ -264,7 +600,7 @@ fn test_draw_without_advantage() {
 fn test_draw_with_advantage() {
     use Suite::*;
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, 1, Suite::PhysicalAg);
     assert_eq!(c, Card::new(9, PhysicalAg));
     assert_eq!(
@@ -272,7 +608,7 @@ fn test_draw_with_advantage() {
         vec![Card::new(10, PhysicalStr), Card::new(9, PhysicalAg)]
     );
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, 2, Suite::MentalAg);
     assert_eq!(c, Card::new(9, PhysicalAg));
     assert_eq!(
@@ -284,7 +620,7 @@ fn test_draw_with_advantage() {
         ]
     );
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, 2, Suite::MentalStr);
     assert_eq!(c, Card::new(8, MentalStr));
     assert_eq!(
@@ -301,7 +637,7 @@ fn test_draw_with_advantage() {
 fn test_draw_with_disadvantage() {
     use Suite::*;
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, -1, Suite::PhysicalAg);
     assert_eq!(c, Card::new(10, PhysicalStr));
     assert_eq!(
@@ -309,7 +645,7 @@ fn test_draw_with_disadvantage() {
         vec![Card::new(10, PhysicalStr), Card::new(9, PhysicalAg)]
     );
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, -2, Suite::MentalAg);
     assert_eq!(c, Card::new(10, PhysicalStr));
     assert_eq!(
@@ -321,7 +657,7 @@ fn test_draw_with_disadvantage() {
         ]
     );
 
-    let mut deck = Deck::new(&fixed_deck);
+    let mut deck = Deck::unshuffled(fixed_deck());
     let (c, d) = draw(&mut deck, -2, Suite::PhysicalStr);
     assert_eq!(c, Card::new(8, MentalStr));
     assert_eq!(
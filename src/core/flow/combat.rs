@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::Instant;
 
 use specs::prelude::*;
@@ -6,7 +6,9 @@ use specs::prelude::*;
 use super::types::*;
 use crate::components::*;
 use crate::core::ai::determine_actor_action;
+use crate::core::ai::plan_boost;
 use crate::core::ai::possible_player_actions;
+use crate::core::ai::suggest_action;
 use crate::core::*;
 
 pub fn init_combat_data<'a, 'b>(
@@ -31,11 +33,37 @@ pub fn init_combat_data<'a, 'b>(
 
     register(&mut world);
 
+    let decks = teams
+        .iter()
+        .map(|t| (t.id, build_deck(t, &actors, &generator)))
+        .collect();
+
     world.insert(map);
     world.insert(generator);
     world.insert(texture_map);
 
-    CombatData::new(CombatState::Init(actors), world, dispatcher, teams)
+    CombatData::new(CombatState::Init(actors), world, dispatcher, teams, decks)
+}
+
+/// The deck of the team with everything the team has won or lost before and
+/// with the changes of the traits of its members (those who are there from
+/// the start)
+fn build_deck(team: &Team, actors: &[Actor], generator: &ObjectGenerator) -> Deck {
+    let mut members = actors
+        .iter()
+        .filter(|a| a.team == team.id)
+        .collect::<Vec<_>>();
+    members.sort_by_key(|a| a.id);
+
+    let trait_changes = members.iter().flat_map(|a| a.deck_changes());
+
+    let changes = team.deck_changes.iter().cloned().chain(trait_changes);
+
+    changes.fold(generator.generate_deck(&team.deck), |deck, c| {
+        // a deck keeps at least one card (no matter how many actors remove
+        // cards from it)
+        deck.clone().change(&c).unwrap_or(deck)
+    })
 }
 
 /// Steps the game one tick forward using the given user input
@@ -79,6 +107,8 @@ fn perform_step<'a, 'b>(
         CombatState::WaitForUserInput(ctxt, selected_pos) => {
             handle_wait_for_user_input(ctxt, selected_pos, user_input, &CoreWorld::new(w))
         }
+
+        CombatState::GameOver(_) => StepResult::new(),
     }
 }
 
@@ -87,11 +117,13 @@ fn find_active_actor(world: &CoreWorld) -> Option<ID> {
 }
 
 fn find_actor_ready_for_activation(turn: &TurnState, world: &CoreWorld) -> Vec<(ID, MapPos, bool)> {
+    let teams = world.teams();
     let candidates = world
         .actors()
         .filter_map(|a| {
             if !a.activations.is_empty() {
-                return Some((a.id, a.pos, a.is_pc(), a.team, a.initiative()));
+                let is_pc = a.is_pc() && !teams.get(&a.team).is_autopiloted(a.id);
+                return Some((a.id, a.pos, is_pc, a.team, a.initiative()));
             }
             None
         })
@@ -121,11 +153,15 @@ fn find_actor_ready_for_activation(turn: &TurnState, world: &CoreWorld) -> Vec<(
         .unwrap();
 
     // final filter: remove all actors which are not part of the priority team
-    candidates
+    // (sorted so every peer of a networked game picks the same one first)
+    let mut result = candidates
         .iter()
         .filter(|(_, _, _, team_id)| *team_id == priority_team)
         .map(|(actor_id, pos, is_pc, _)| (*actor_id, MapPos::from_world_pos(*pos), *is_pc))
-        .collect::<Vec<_>>()
+        .collect::<Vec<_>>();
+
+    result.sort_by_key(|(actor_id, ..)| *actor_id);
+    result
 }
 
 fn handle_wait_until(t: &Instant, remaining_actions: &Vec<Action>) -> StepResult {
@@ -184,11 +220,19 @@ fn handle_wait_for_user_input(
         }
 
         Some(UserInput::BoostActivation(actor_id, team_id, card)) => {
-            // let mut team_data = w.teams().get(team_id).clone();
-            //     let card = team_data.hand.remove(*card_idx);
+            let mut team_data = w.teams().get(team_id).clone();
+
+            if let Some(actor) = w.get_actor(*actor_id) {
+                // remember how things were so the assignment can be undone
+                team_data.planned_boosts.push(PlannedBoost {
+                    actor: *actor_id,
+                    hand: team_data.hand.clone(),
+                    activations: actor.activations.clone(),
+                });
+            }
 
             return StepResult::new()
-                // .modify_team(team_data)
+                .modify_team(team_data)
                 .remove_card_from_hand(*team_id, *card)
                 .switch_state(CombatState::ResolveAction(vec![Action::BoostActivation(
                     *actor_id, *card,
@@ -198,17 +242,77 @@ fn handle_wait_for_user_input(
         Some(UserInput::AssigneActivationDone(team_id)) => {
             let mut team_data = w.teams().get(team_id).clone();
             team_data.ready = true;
+            team_data.planned_boosts.clear();
+
+            return StepResult::new()
+                .modify_team(team_data)
+                .switch_state(CombatState::AssignActivations());
+        }
+
+        Some(UserInput::UndoBoost(team_id)) => {
+            let mut team_data = w.teams().get(team_id).clone();
+
+            if let Some(PlannedBoost {
+                actor,
+                hand,
+                activations,
+            }) = team_data.planned_boosts.pop()
+            {
+                team_data.hand = hand;
+
+                return StepResult::new().modify_team(team_data).switch_state(
+                    CombatState::ResolveAction(vec![Action::ResetActivations(actor, activations)]),
+                );
+            }
+        }
+
+        Some(UserInput::HandOverDone(team_id)) => {
+            let mut team_data = w.teams().get(team_id).clone();
+            team_data.handed_over = true;
 
             return StepResult::new()
                 .modify_team(team_data)
                 .switch_state(CombatState::AssignActivations());
         }
 
-        Some(UserInput::SelectPlayerAction(action)) => {
+        Some(UserInput::AutoResolveTurn(team_id)) => {
+            let mut team_data = w.teams().get(team_id).clone();
+            team_data.autopilot = true;
+
+            return StepResult::new()
+                .modify_team(team_data)
+                .switch_state(CombatState::FindActor());
+        }
+
+        Some(UserInput::ToggleAutopilot(team_id, actor_id)) => {
+            let mut team_data = w.teams().get(team_id).clone();
+            if !team_data.autopilot_actors.remove(actor_id) {
+                team_data.autopilot_actors.insert(*actor_id);
+            }
+
+            return StepResult::new()
+                .modify_team(team_data)
+                .switch_state(CombatState::FindActor());
+        }
+
+        Some(UserInput::ToggleAdvisor(team_id)) => {
+            let mut team_data = w.teams().get(team_id).clone();
+            team_data.advisor = !team_data.advisor;
+
+            return StepResult::new()
+                .modify_team(team_data)
+                .switch_state(CombatState::FindActor());
+        }
+
+        Some(UserInput::SelectPlayerAction(pos, idx)) => {
             // user has selected an action
             // => resolve that action
-            return StepResult::new()
-                .switch_state(CombatState::ResolveAction(vec![action.clone()]));
+            if let InputContext::SelectAction { options, .. } = ctxt {
+                if let Some(action) = options.get(pos).and_then(|actions| actions.get(*idx)) {
+                    return StepResult::new()
+                        .switch_state(CombatState::ResolveAction(vec![action.clone()]));
+                }
+            }
         }
 
         // no user input
@@ -247,7 +351,43 @@ fn handle_init(game_objects: &Vec<Actor>, w: &World) -> StepResult {
     StepResult::new().switch_state(CombatState::StartTurn())
 }
 
+/// The teams which have won the battle if it is over: that is once the teams
+/// left standing are no longer hostile to each other (neutral teams do not
+/// fight) and no hostile reinforcements are coming or once none of the human
+/// players is left standing
+pub fn find_winners(turn: &TurnState, world: &CoreWorld) -> Option<Vec<TeamId>> {
+    let teams = world.teams();
+    let mut standing = world
+        .actors()
+        .filter(|a| a.is_concious())
+        .map(|a| a.team)
+        .collect::<Vec<_>>();
+
+    standing.sort();
+    standing.dedup();
+
+    let is_hostile_to_anyone = |t: TeamId| standing.iter().any(|s| teams.is_hostile(*s, t));
+    let is_fighting = standing.iter().any(|t| is_hostile_to_anyone(*t));
+    let is_threatened = turn
+        .upcoming_reinforcements()
+        .iter()
+        .any(|(_, t, _)| is_hostile_to_anyone(*t));
+
+    let has_humans = teams.iter().any(|td| td.team.is_pc);
+    let are_humans_beaten = !standing.iter().any(|t| teams.get(t).team.is_pc);
+
+    if (!is_fighting && !is_threatened) || (has_humans && are_humans_beaten) {
+        Some(standing)
+    } else {
+        None
+    }
+}
+
 fn handle_find_actor(turn: &TurnState, world: &CoreWorld) -> StepResult {
+    if let Some(winners) = find_winners(turn, world) {
+        return StepResult::new().switch_state(CombatState::GameOver(winners));
+    }
+
     if let CombatPhase::Planning = turn.phase {
         // this can happen after advancing the game or resolving an action
         return StepResult::new().switch_state(CombatState::AssignActivations());
@@ -278,7 +418,10 @@ fn handle_find_actor(turn: &TurnState, world: &CoreWorld) -> StepResult {
                     .collect::<HashMap<_, _>>();
 
                 StepResult::new().switch_state(CombatState::WaitForUserInput(
-                    InputContext::SelectAction { options },
+                    InputContext::SelectAction {
+                        options,
+                        suggestion: None,
+                    },
                     Some(SelectedPos {
                         pos: selected_pos,
                         objects: find_actors_at(selected_pos, world),
@@ -315,8 +458,20 @@ fn handle_start_turn(world: CoreWorld, turn: &TurnState) -> StepResult {
     let mut actions: Vec<Action> = Vec::new();
     let mut actor_per_team: HashMap<TeamId, u8> = HashMap::new();
     let mut step_result = StepResult::new();
+    let mut occupied = world
+        .collect_obstacles()
+        .keys()
+        .copied()
+        .collect::<HashSet<_>>();
+    let mut delayed = vec![];
 
     for (team, pos, template) in turn.reinforcements() {
+        if !occupied.insert(pos) {
+            // someone blocks the spawn point -> try again next turn
+            delayed.push((team, pos, template));
+            continue;
+        }
+
         let actor = world.generate_enemy(pos, team, template);
         let curr_amout = actor_per_team.get(&actor.team).copied().unwrap_or(0);
         let actor_id = actor.id;
@@ -326,7 +481,12 @@ fn handle_start_turn(world: CoreWorld, turn: &TurnState) -> StepResult {
         actions.push(Action::StartTurn(actor_id));
     }
 
-    for a in world.actors() {
+    // the order of the entities may differ between the peers of a networked
+    // game but everyone has to draw the same cards for the same actor
+    let mut actors = world.actors().collect::<Vec<_>>();
+    actors.sort_by_key(|a| a.id);
+
+    for a in actors {
         actions.push(Action::StartTurn(a.id));
 
         let curr_amout = actor_per_team.get(&a.team).copied().unwrap_or(0);
@@ -339,6 +499,10 @@ fn handle_start_turn(world: CoreWorld, turn: &TurnState) -> StepResult {
         step_result = step_result.start_new_turn(*team_id, num_draws);
     }
 
+    if !delayed.is_empty() {
+        step_result = step_result.advance_game(turn.clone().delay_reinforcements(&delayed));
+    }
+
     step_result.switch_state(CombatState::ResolveAction(actions))
 }
 
@@ -357,7 +521,18 @@ fn handle_assign_activations(turn: &TurnState, world: &CoreWorld) -> StepResult
         return StepResult::new().switch_state(CombatState::AdvanceGame());
     }
 
-    if active_team.team.is_pc {
+    if active_team.team.is_pc && !active_team.autopilot {
+        if teams.is_hot_seat() && !active_team.handed_over {
+            // Several human players share the screen
+            // => hide everything until the right player has taken over
+            return StepResult::new().switch_state(CombatState::WaitForUserInput(
+                InputContext::HandOver {
+                    team: active_team.team.id,
+                },
+                None,
+            ));
+        }
+
         // The team is controlled by the a human player
         // => wait for the user's to distribute hand
         StepResult::new().switch_state(CombatState::WaitForUserInput(
@@ -371,7 +546,15 @@ fn handle_assign_activations(turn: &TurnState, world: &CoreWorld) -> StepResult
         ))
     } else {
         // The team is controlled by the AI
-        // => no hand for the AI, so continue with the next step
+        // => let the AI play its cards one by one until it wants to keep the rest
+        if let Some((actor_id, card)) = plan_boost(active_team, world) {
+            return StepResult::new()
+                .remove_card_from_hand(active_team.team.id, card)
+                .switch_state(CombatState::ResolveAction(vec![Action::BoostActivation(
+                    actor_id, card,
+                )]));
+        }
+
         let mut team_data = active_team.clone();
         team_data.ready = true;
         StepResult::new().modify_team(team_data)
@@ -385,18 +568,28 @@ fn handle_select_player_action(id: ID, w: CoreWorld) -> StepResult {
     }
 
     let actor = a.unwrap().clone();
-    if actor.is_pc() {
+    let team = w.teams().get(&actor.team).clone();
+    let (known_enemies, newly_spotted) = spot_enemies(&actor, &w);
+    let result = StepResult::new().spot_enemies(actor.team, newly_spotted);
+
+    if actor.is_pc() && !team.is_autopiloted(actor.id) {
         // the next ready actor is a player controlled entity
         // => wait for user input;
         let options = possible_player_actions(&actor, &w);
         if let Some(action) = single_option(&options) {
-            StepResult::new().switch_state(CombatState::ResolveAction(vec![action]))
+            result.switch_state(CombatState::ResolveAction(vec![action]))
         } else {
             let selected_pos = MapPos::from_world_pos(actor.pos);
+            let suggestion = if team.advisor {
+                suggest_action(&actor, &known_enemies, &options, &w)
+            } else {
+                None
+            };
 
-            StepResult::new().switch_state(CombatState::WaitForUserInput(
+            result.switch_state(CombatState::WaitForUserInput(
                 InputContext::SelectAction {
-                    options: possible_player_actions(&actor, &w),
+                    options,
+                    suggestion,
                 },
                 Some(SelectedPos {
                     pos: selected_pos,
@@ -405,14 +598,32 @@ fn handle_select_player_action(id: ID, w: CoreWorld) -> StepResult {
             ))
         }
     } else {
-        // the next ready actor is a player controlled entity
+        // the next ready actor is controlled by the AI
         // => let the AI compute an action and resolve it
         //    so far we have no reactions
-        let action = determine_actor_action(&actor, w);
-        StepResult::new().switch_state(CombatState::ResolveAction(vec![action]))
+        let action = determine_actor_action(&actor, &known_enemies, w);
+        result.switch_state(CombatState::ResolveAction(vec![action]))
     }
 }
 
+/// Returns the enemies the team of the actor knows about (including those
+/// the actor spots right now) and those which have been newly spotted
+fn spot_enemies(actor: &Actor, w: &CoreWorld) -> (HashSet<ID>, Vec<ID>) {
+    let vision = team_vision(actor.team, w);
+    let teams = w.teams();
+    let mut known_enemies = teams.get(&actor.team).spotted.clone();
+    let newly_spotted = w
+        .actors()
+        .filter(|a| teams.is_hostile(a.team, actor.team) && !known_enemies.contains(&a.id))
+        .filter(|a| vision.can_see_actor(a))
+        .map(|a| a.id)
+        .collect::<Vec<_>>();
+
+    known_enemies.extend(newly_spotted.iter().copied());
+
+    (known_enemies, newly_spotted)
+}
+
 fn handle_resolve_action(actions: &Vec<Action>, w: &World) -> StepResult {
     if actions.is_empty() {
         return StepResult::new().switch_state(CombatState::FindActor());
@@ -421,6 +632,7 @@ fn handle_resolve_action(actions: &Vec<Action>, w: &World) -> StepResult {
     let mut remaining_actions = actions.to_vec();
     let mut wait_until = Instant::now();
     let action = remaining_actions.remove(0);
+    let provoked_teams = provoke(&action, &CoreWorld::new(w));
     let ActionResult {
         decks,
         fx_seq,
@@ -430,12 +642,26 @@ fn handle_resolve_action(actions: &Vec<Action>, w: &World) -> StepResult {
 
     let mut result = StepResult::new().add_score(score).append_log(log);
 
+    for team_data in provoked_teams {
+        result = result.modify_team(team_data);
+    }
+
     if let Some(mut draws) = decks {
         for (team_id, deck) in draws.drain() {
             result = result.update_deck(team_id, deck);
         }
     }
 
+    if w.has_value::<Headless>() {
+        // nobody is watching
+        // => skip the animations and apply the changes right away
+        for eff in fx_seq.into_state_changes() {
+            apply_now(eff, w);
+        }
+
+        return result.switch_state(CombatState::WaitUntil(wait_until, remaining_actions));
+    }
+
     for fx in fx_seq.into_fx_vec(Instant::now()).drain(..) {
         if wait_until < fx.ends_at() {
             wait_until = fx.ends_at();
@@ -447,6 +673,63 @@ fn handle_resolve_action(actions: &Vec<Action>, w: &World) -> StepResult {
     result.switch_state(CombatState::WaitUntil(wait_until, remaining_actions))
 }
 
+/// Attacking a neutral team makes it hostile (for both sides); returns the
+/// changed teams
+fn provoke(action: &Action, cw: &CoreWorld) -> Vec<TeamData> {
+    if let Action::Attack {
+        attacker, target, ..
+    } = action
+    {
+        if let (Some(a), Some(t)) = (cw.get_actor(*attacker), cw.get_actor(*target)) {
+            let teams = cw.teams();
+
+            if teams.relation(a.team, t.team) == Relation::Neutral {
+                let mut attacking_team = teams.get(&a.team).clone();
+                let mut attacked_team = teams.get(&t.team).clone();
+
+                attacking_team.set_relation(t.team, Relation::Hostile);
+                attacked_team.set_relation(a.team, Relation::Hostile);
+
+                return vec![attacking_team, attacked_team];
+            }
+        }
+    }
+
+    vec![]
+}
+
+/// Applies a change to the game state right away (without any animation)
+fn apply_now(eff: FxEffect, w: &World) {
+    let (entities, actors, updater): (Entities, ReadStorage<ActorCmp>, Read<LazyUpdate>) =
+        w.system_data();
+
+    let find_entity = |id: ID| {
+        (&entities, &actors)
+            .join()
+            .find(|(_, ActorCmp(a))| a.id == id)
+            .map(|(e, _)| e)
+    };
+
+    match eff {
+        FxEffect::Remove(id) => {
+            if let Some(e) = find_entity(id) {
+                let _ = entities.delete(e);
+            }
+        }
+
+        FxEffect::Update(a) => {
+            // usually the movement animation takes care of the position
+            if let Some(e) = find_entity(a.id) {
+                updater.insert(e, Position(a.pos));
+            }
+
+            Fx::now(FxEffect::Update(a)).run(w);
+        }
+
+        eff => Fx::now(eff).run(w),
+    }
+}
+
 fn spawn_obstacles(w: &World) {
     let (_map, texture_map, updater, entities): (
         Read<Map>,
@@ -462,19 +745,12 @@ fn spawn_obstacles(w: &World) {
         (8.0, 9.0),
         (10.0, 7.0),
     ];
-    let sprite = texture_map.get("wall-1").unwrap();
 
     for (x, y) in pos.iter() {
-        updater
-            .create_entity(&entities)
-            .with(Sprites::new(vec![sprite.clone()]))
-            .with(Position(WorldPos::new(*x, *y, 0.0)))
-            .with(ZLayerGameObject)
-            .with(ObstacleCmp {
-                movement: (Some(Obstacle::Blocker), Some(Obstacle::Blocker), None),
-                reach: Some(Hitbox::new_pillar()),
-            })
-            .build();
+        let wall = Structure::new_wall(WorldPos::new(*x, *y, 0.0));
+        let e = insert_structure(&wall, &entities, &updater);
+
+        update_structure(e, &wall, &texture_map, &updater);
     }
 }
 
@@ -501,3 +777,170 @@ fn activ_team_members(active_team: &Team, w: &CoreWorld) -> HashMap<MapPos, ID>
     }
     team_members
 }
+
+#[test]
+fn test_card_assignments_can_be_undone() {
+    use super::{create_player_chars, create_team_cpu, create_team_player};
+
+    let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+    let actors = create_player_chars(&generator);
+    let teams = vec![create_team_player(), create_team_cpu()];
+    let mut data = init_combat_data(actors, teams, generator, TextureMap::default());
+
+    data.world.insert(Headless);
+
+    let wait_for_planning = |mut data: CombatData<'static, 'static>| {
+        for _ in 0..1000 {
+            if let CombatState::WaitForUserInput(InputContext::ActivateActor { .. }, _) = data.state
+            {
+                return data;
+            }
+            data = step(data, &None);
+        }
+        panic!("The player never gets to plan");
+    };
+
+    let data = wait_for_planning(data);
+    let (team, hand, actor) = match &data.state {
+        CombatState::WaitForUserInput(
+            InputContext::ActivateActor {
+                team,
+                hand,
+                possible_actors,
+                ..
+            },
+            _,
+        ) => (
+            *team,
+            hand.clone(),
+            *possible_actors.values().min().unwrap(),
+        ),
+        _ => unreachable!(),
+    };
+    let activations = |data: &CombatData| {
+        let cw = CoreWorld::new(&data.world);
+        cw.get_actor(actor).unwrap().activations.clone()
+    };
+    let activations_before = activations(&data);
+
+    let data = step(
+        data,
+        &Some(UserInput::BoostActivation(actor, team, hand[0])),
+    );
+    let data = wait_for_planning(data);
+
+    assert_ne!(activations(&data), activations_before);
+
+    let data = step(data, &Some(UserInput::UndoBoost(team)));
+    let data = wait_for_planning(data);
+    let cw = CoreWorld::new(&data.world);
+
+    assert_eq!(cw.teams().get(&team).hand, hand);
+    assert!(cw.teams().get(&team).planned_boosts.is_empty());
+    drop(cw);
+    assert_eq!(activations(&data), activations_before);
+}
+
+#[test]
+fn test_initiative_order_predicts_the_next_actor() {
+    use super::{create_player_chars, create_team_cpu, create_team_player};
+
+    let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+    let actors = create_player_chars(&generator);
+    let teams = vec![create_team_player(), create_team_cpu()];
+    let mut data = init_combat_data(actors, teams, generator, TextureMap::default());
+    let mut num_checks = 0;
+
+    data.world.insert(Headless);
+
+    for _ in 0..1000 {
+        if let (CombatState::FindActor(), CombatPhase::Action) = (&data.state, &data.turn.phase) {
+            let cw = CoreWorld::new(&data.world);
+
+            if find_active_actor(&cw).is_none() {
+                let candidates = find_actor_ready_for_activation(&data.turn, &cw);
+                let next = data.initiative_order().first().map(|(id, _)| *id);
+
+                assert_eq!(next, candidates.first().map(|(id, ..)| *id));
+                num_checks += 1;
+            }
+        }
+
+        let input = data.waiting_for().map(UserInput::AutoResolveTurn);
+        data = step(data, &input);
+    }
+
+    assert!(num_checks > 0, "No actor has been activated");
+}
+
+#[test]
+fn test_blocked_reinforcements_arrive_one_turn_later() {
+    use super::{create_player_chars, create_team_cpu, create_team_player};
+
+    let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+    let actors = create_player_chars(&generator);
+    let mut team_cpu = create_team_cpu();
+    let blocked_pos = MapPos(1, 6);
+
+    // a second sucker wants to arrive at the same tile in the 1st turn
+    if let Some(reinforcements) = &mut team_cpu.reinforcements {
+        reinforcements.push((1, blocked_pos, ActorTemplateName::new("enemy#sucker")));
+    }
+
+    let teams = vec![create_team_player(), team_cpu];
+    let mut data = init_combat_data(actors, teams, generator, TextureMap::default());
+
+    data.world.insert(Headless);
+
+    for _ in 0..1000 {
+        if let CombatState::WaitForUserInput(InputContext::ActivateActor { .. }, _) = data.state {
+            break;
+        }
+        data = step(data, &None);
+    }
+
+    let cw = CoreWorld::new(&data.world);
+    let num_actors_at_pos = cw
+        .actors()
+        .filter(|a| MapPos::from_world_pos(a.pos) == blocked_pos)
+        .count();
+
+    let upcoming = data.turn.upcoming_reinforcements();
+
+    assert_eq!(num_actors_at_pos, 1);
+    assert!(upcoming.contains(&(1, TeamId::new(super::TEAM_CPU), blocked_pos)));
+}
+
+#[test]
+fn test_the_battle_is_over_once_no_hostile_team_is_left() {
+    use super::{create_player_chars, create_team_cpu, create_team_player, TEAM_CPU, TEAM_PLAYER};
+
+    let run_until_decision = |teams: Vec<Team>| {
+        let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+        let actors = create_player_chars(&generator);
+        let mut data = init_combat_data(actors, teams, generator, TextureMap::default());
+
+        data.world.insert(Headless);
+
+        for _ in 0..1000 {
+            match data.state {
+                CombatState::GameOver(..) | CombatState::WaitForUserInput(..) => break,
+                _ => data = step(data, &None),
+            }
+        }
+
+        data.state
+    };
+
+    // the computer is hostile and sends its first wave right away
+    let state = run_until_decision(vec![create_team_player(), create_team_cpu()]);
+    assert!(matches!(state, CombatState::WaitForUserInput(..)));
+
+    // neutral onlookers do not fight
+    let mut team_neutral = create_team_cpu();
+    team_neutral.relations = vec![(TeamId::new(TEAM_PLAYER), Relation::Neutral)];
+
+    let state = run_until_decision(vec![create_team_player(), team_neutral]);
+    let winners = vec![TeamId::new(TEAM_PLAYER), TeamId::new(TEAM_CPU)];
+    assert!(matches!(state, CombatState::GameOver(w) if w == winners));
+}
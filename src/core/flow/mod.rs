@@ -1,54 +1,61 @@
 mod combat;
+mod simulation;
 mod types;
 
 use crate::core::*;
 
-use combat::init_combat_data;
+pub(super) use combat::init_combat_data;
 
+pub use simulation::*;
 pub use types::*;
 
 const TEAM_PLAYER: u8 = 1;
 const TEAM_CPU: u8 = 2;
+const TEAM_MILITIA: u8 = 3;
+const TEAM_RAIDERS: u8 = 4;
+
+/// Names and starting positions of the teams in a hot-seat game (the names
+/// match the team colours, see TeamId::colour)
+const HOT_SEAT_TEAMS: [(&str, [(u8, u8); 3]); 4] = [
+    ("Blue", [(1, 6), (1, 7), (2, 6)]),
+    ("Red", [(11, 6), (11, 7), (10, 6)]),
+    ("Green", [(5, 1), (6, 1), (7, 1)]),
+    ("Yellow", [(5, 11), (7, 11), (6, 12)]),
+];
 
 pub fn step<'a, 'b>(g: Game<'a, 'b>, i: &Option<UserInput>) -> Game<'a, 'b> {
     match g {
         Game::Start(gen, tex_map) => start_step(gen, tex_map, i),
 
-        Game::TeamSelection(gen, tex_map, team) => teams_step(gen, tex_map, team, i),
+        Game::TeamSelection(gen, tex_map, actors, teams) => {
+            teams_step(gen, tex_map, actors, teams, i)
+        }
 
         Game::Combat(combat_data) => Game::Combat(combat::step(combat_data, i)),
     }
 }
 
 fn start_step<'a, 'b>(g: ObjectGenerator, tm: TextureMap, i: &Option<UserInput>) -> Game<'a, 'b> {
-    let team_id_player = TeamId::new(TEAM_PLAYER);
-
     match i {
         Some(UserInput::NewGame) => {
-            let player_chars = vec![
-                (g.generate_player(
-                    WorldPos::new(7.0, 6.0, 0.0),
-                    team_id_player,
-                    ActorTemplateName::new("actor#tank"),
-                )),
-                (g.generate_player(
-                    WorldPos::new(8.0, 6.0, 0.0),
-                    team_id_player,
-                    ActorTemplateName::new("actor#saw"),
-                )),
-                (g.generate_player(
-                    WorldPos::new(7.0, 7.0, 0.0),
-                    team_id_player,
-                    ActorTemplateName::new("actor#spear"),
-                )),
-                (g.generate_player(
-                    WorldPos::new(8.0, 7.0, 0.0),
-                    team_id_player,
-                    ActorTemplateName::new("actor#gunner"),
-                )),
-            ];
-
-            Game::TeamSelection(g, tm, player_chars)
+            let player_chars = create_player_chars(&g);
+            let teams = vec![create_team_player(), create_team_cpu()];
+
+            Game::TeamSelection(g, tm, player_chars, teams)
+        }
+
+        Some(UserInput::NewThreeWayBattle) => {
+            let player_chars = create_player_chars(&g);
+            let teams = create_three_way_teams();
+
+            Game::TeamSelection(g, tm, player_chars, teams)
+        }
+
+        Some(UserInput::NewHotSeatGame(num_teams)) => {
+            let teams = create_hot_seat_teams(*num_teams);
+            let player_chars = create_hot_seat_chars(&g, &teams);
+
+            Game::TeamSelection(g, tm, player_chars, teams)
         }
 
         _ => Game::Start(g, tm),
@@ -59,34 +66,136 @@ fn teams_step<'a, 'b>(
     g: ObjectGenerator,
     tm: TextureMap,
     t: Vec<Actor>,
+    teams: Vec<Team>,
     i: &Option<UserInput>,
 ) -> Game<'a, 'b> {
     match i {
-        Some(UserInput::SelectTeam(..)) => Game::Combat(init_combat_data(
-            t,
-            vec![create_team_player(), create_team_cpu()],
-            g,
-            tm,
+        Some(UserInput::SelectTeam(..)) => Game::Combat(init_combat_data(t, teams, g, tm)),
+
+        _ => Game::TeamSelection(g, tm, t, teams),
+    }
+}
+
+pub(super) fn create_player_chars(g: &ObjectGenerator) -> Vec<Actor> {
+    let team_id_player = TeamId::new(TEAM_PLAYER);
+
+    vec![
+        (g.generate_player(
+            WorldPos::new(7.0, 6.0, 0.0),
+            team_id_player,
+            ActorTemplateName::new("actor#tank"),
+        )),
+        (g.generate_player(
+            WorldPos::new(8.0, 6.0, 0.0),
+            team_id_player,
+            ActorTemplateName::new("actor#saw"),
+        )),
+        (g.generate_player(
+            WorldPos::new(7.0, 7.0, 0.0),
+            team_id_player,
+            ActorTemplateName::new("actor#spear"),
         )),
+        (g.generate_player(
+            WorldPos::new(8.0, 7.0, 0.0),
+            team_id_player,
+            ActorTemplateName::new("actor#gunner"),
+        )),
+        (g.generate_player(
+            WorldPos::new(6.0, 7.0, 0.0),
+            team_id_player,
+            ActorTemplateName::new("actor#medic"),
+        )),
+    ]
+}
+
+/// Starts a game between two human teams on different machines (see
+/// lockstep networking); the host plays the first team, the other peer the
+/// second one
+pub fn network_game<'a, 'b>(g: ObjectGenerator, tm: TextureMap, is_host: bool) -> Game<'a, 'b> {
+    let mut teams = create_hot_seat_teams(2);
+    let remote_idx = if is_host { 1 } else { 0 };
+
+    teams[remote_idx].is_remote = true;
+
+    let player_chars = create_hot_seat_chars(&g, &teams);
 
-        _ => Game::TeamSelection(g, tm, t),
+    Game::Combat(init_combat_data(player_chars, teams, g, tm))
+}
+
+fn create_hot_seat_chars(g: &ObjectGenerator, teams: &[Team]) -> Vec<Actor> {
+    let templates = ["actor#tank", "actor#spear", "actor#gunner"];
+    let mut result = vec![];
+
+    for (t, (_, positions)) in teams.iter().zip(HOT_SEAT_TEAMS.iter()) {
+        for ((x, y), template) in positions.iter().zip(templates.iter()) {
+            result.push(g.generate_player(
+                WorldPos::new(*x as f32, *y as f32, 0.0),
+                t.id,
+                ActorTemplateName::new(template),
+            ));
+        }
     }
+
+    result
 }
 
-fn create_team_player() -> Team {
+/// Creates 2 to 4 human teams which fight each other (there are no
+/// computer controlled enemies in a hot-seat game)
+fn create_hot_seat_teams(num_teams: u8) -> Vec<Team> {
+    let num_teams = num_teams.max(2).min(HOT_SEAT_TEAMS.len() as u8);
+
+    HOT_SEAT_TEAMS
+        .iter()
+        .take(num_teams as usize)
+        .enumerate()
+        .map(|(idx, (name, _))| Team {
+            name,
+            id: TeamId::new(TEAM_PLAYER + idx as u8),
+            is_pc: true,
+            is_remote: false,
+            relations: vec![],
+            deck: DeckName::new("deck#standard"),
+            deck_changes: vec![],
+            reinforcements: None,
+        })
+        .collect()
+}
+
+/// The player and the militia against monsters and raiders
+fn create_three_way_teams() -> Vec<Team> {
+    let mut team_player = create_team_player();
+    team_player.relations = vec![(TeamId::new(TEAM_MILITIA), Relation::Allied)];
+
+    vec![
+        team_player,
+        create_team_militia(),
+        create_team_cpu(),
+        create_team_raiders(),
+    ]
+}
+
+pub(super) fn create_team_player() -> Team {
     Team {
         name: "Player",
         id: TeamId::new(TEAM_PLAYER),
         is_pc: true,
+        is_remote: false,
+        relations: vec![],
+        deck: DeckName::new("deck#standard"),
+        deck_changes: vec![],
         reinforcements: None,
     }
 }
 
-fn create_team_cpu() -> Team {
+pub(super) fn create_team_cpu() -> Team {
     Team {
         name: "Computer",
         id: TeamId::new(TEAM_CPU),
         is_pc: false,
+        is_remote: false,
+        relations: vec![],
+        deck: DeckName::new("deck#standard"),
+        deck_changes: vec![],
         reinforcements: Some(vec![
             // initial (1st) wave
             (1, MapPos(1, 6), ActorTemplateName::new("enemy#sucker")),
@@ -102,3 +211,37 @@ fn create_team_cpu() -> Team {
         ]),
     }
 }
+
+fn create_team_militia() -> Team {
+    Team {
+        name: "Militia",
+        id: TeamId::new(TEAM_MILITIA),
+        is_pc: false,
+        is_remote: false,
+        relations: vec![(TeamId::new(TEAM_PLAYER), Relation::Allied)],
+        deck: DeckName::new("deck#militia"),
+        deck_changes: vec![],
+        reinforcements: Some(vec![
+            (1, MapPos(9, 8), ActorTemplateName::new("actor#spear")),
+            (1, MapPos(10, 8), ActorTemplateName::new("actor#tank")),
+        ]),
+    }
+}
+
+fn create_team_raiders() -> Team {
+    Team {
+        name: "Raiders",
+        id: TeamId::new(TEAM_RAIDERS),
+        is_pc: false,
+        is_remote: false,
+        relations: vec![],
+        deck: DeckName::new("deck#raiders"),
+        deck_changes: vec![],
+        reinforcements: Some(vec![
+            (1, MapPos(12, 6), ActorTemplateName::new("actor#saw")),
+            (1, MapPos(12, 7), ActorTemplateName::new("actor#gunner")),
+            (4, MapPos(11, 9), ActorTemplateName::new("actor#saw")),
+            (4, MapPos(10, 10), ActorTemplateName::new("actor#tank")),
+        ]),
+    }
+}
@@ -0,0 +1,101 @@
+use specs::prelude::*;
+
+use super::combat::{self, init_combat_data};
+use super::{create_player_chars, create_team_cpu, create_team_player, TEAM_PLAYER};
+use crate::components::ActorCmp;
+use crate::core::*;
+
+/// The maximum number of turns before a simulated combat is called a draw
+const MAX_TURNS: u64 = 30;
+
+/// Safety net in case the AI gets stuck
+const MAX_STEPS: u64 = 100_000;
+
+/// Marks a world which is not displayed; all animations are skipped and
+/// changes are applied right away
+#[derive(Debug, Default)]
+pub struct Headless;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SimulationOutcome {
+    Victory,
+    Defeat,
+    Draw,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub outcome: SimulationOutcome,
+    pub turns: u64,
+    pub survivors: usize,
+    pub score: u64,
+}
+
+/// Plays a complete combat without any display or user input; the player's
+/// team is controlled by the AI with the given behaviour
+pub fn simulate(generator: ObjectGenerator, player_ai: AiBehaviour) -> SimulationResult {
+    let mut team_player = create_team_player();
+    team_player.is_pc = false;
+
+    let actors = create_player_chars(&generator)
+        .drain(..)
+        .map(|mut a| {
+            a.behaviour = Some(player_ai.clone());
+            a
+        })
+        .collect();
+
+    let mut data = init_combat_data(
+        actors,
+        vec![team_player, create_team_cpu()],
+        generator,
+        TextureMap::default(),
+    );
+
+    data.world.insert(Headless);
+
+    for _ in 0..MAX_STEPS {
+        data = combat::step(data, &None);
+
+        if let Some(outcome) = outcome(&data) {
+            return SimulationResult {
+                outcome,
+                turns: data.turn.turn_number,
+                survivors: survivors(&data.world),
+                score: data.score,
+            };
+        }
+    }
+
+    SimulationResult {
+        outcome: SimulationOutcome::Draw,
+        turns: data.turn.turn_number,
+        survivors: survivors(&data.world),
+        score: data.score,
+    }
+}
+
+/// The player wins if their team is among those left standing when the
+/// battle is over (see combat::find_winners)
+fn outcome(data: &CombatData) -> Option<SimulationOutcome> {
+    match &data.state {
+        CombatState::GameOver(winners) if winners.contains(&TeamId::new(TEAM_PLAYER)) => {
+            Some(SimulationOutcome::Victory)
+        }
+
+        CombatState::GameOver(_) => Some(SimulationOutcome::Defeat),
+
+        _ if data.turn.turn_number > MAX_TURNS => Some(SimulationOutcome::Draw),
+
+        _ => None,
+    }
+}
+
+fn survivors(w: &World) -> usize {
+    let team_player = TeamId::new(TEAM_PLAYER);
+
+    w.read_storage::<ActorCmp>()
+        .join()
+        .filter(|ActorCmp(a)| a.team == team_player && a.is_concious())
+        .count()
+}
@@ -1,30 +1,69 @@
 use core::panic;
 use std::{
     cmp::Ordering,
-    collections::{BTreeMap, HashMap},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap, HashSet},
+    hash::{Hash, Hasher},
     time::Instant,
 };
 
 use specs::prelude::*;
 
 use crate::core::{
-    ai::PlayerActionOptions, Action, Actor, ActorTemplateName, Card, Deck, DisplayStr, MapPos,
-    ObjectGenerator, Team, TeamId, TextureMap, ID,
+    ai::PlayerActionOptions, write_to_log_file, Action, Activation, Actor, ActorTemplateName, Card,
+    CoreWorld, Deck, LogEvent, MapPos, ObjectGenerator, Relation, Team, TeamId, TextureMap, ID,
 };
 
+/// The number of log events which are kept for the log panel
+const MAX_LOG_EVENTS: usize = 500;
+
 #[derive(Debug, Clone)]
 pub enum UserInput {
     Exit(),
     NewGame,
+    /// Starts a local game for the given number of human teams (sharing one screen)
+    NewHotSeatGame(u8),
+    /// Starts a game where the player and the militia fight against monsters
+    /// and raiders (who do not like each other either)
+    NewThreeWayBattle,
     SelectTeam(Vec<Actor>),
-    SelectPlayerAction(Action),
+    /// The position and the index of the selected action (see
+    /// InputContext::SelectAction)
+    SelectPlayerAction(MapPos, usize),
     SelectActivationCard(usize),
     BoostActivation(ID, TeamId, Card),
     AssigneActivationDone(TeamId),
+    HandOverDone(TeamId),
+    AutoResolveTurn(TeamId),
+    ToggleAutopilot(TeamId, ID),
+    ToggleAdvisor(TeamId),
+    /// Takes back the last card the team has assigned in this planning phase
+    UndoBoost(TeamId),
     SelectWorldPos(MapPos),
+    /// The mouse has moved to the given screen position
+    Hover(i32, i32),
     StartScrolling(),
     EndScrolling(),
     ScrollTo(i32, i32),
+    /// Moves the camera by the given number of pixels (e.g. with the keyboard)
+    ScrollBy(i32, i32),
+    /// Keeps moving the camera in the given direction (the mouse is at the
+    /// edge of the window) until the direction is (0, 0) again
+    EdgeScroll(i32, i32),
+    /// Zooms in (positive) or out (negative) around the given screen position
+    Zoom(i32, (i32, i32)),
+    /// Shows older (positive) or newer (negative) events in the combat log
+    ScrollLog(i32),
+    ToggleDeckViewer,
+    ToggleMinimap,
+    /// Shows (or hides) the video settings
+    ToggleOptions,
+    SetResolution(u32, u32),
+    ToggleFullscreen,
+    ToggleVsync,
+    /// The window has a new size (in pixels)
+    WindowResized(u32, u32),
+    /// Saves the current screen as image
+    Screenshot,
 }
 
 #[derive(Debug, Clone)]
@@ -37,18 +76,24 @@ pub enum InputContext {
     },
     SelectAction {
         options: PlayerActionOptions,
+        /// The position and the index of the action the AI advises (if the
+        /// advisor is on)
+        suggestion: Option<(MapPos, usize)>,
     },
+    /// Hides everything until the next (hot-seat) team has taken over the
+    /// controls so no one sees the hand of another team
+    HandOver { team: TeamId },
 }
 
 pub enum Game<'a, 'b> {
     Start(ObjectGenerator, TextureMap),
-    TeamSelection(ObjectGenerator, TextureMap, Vec<Actor>),
+    TeamSelection(ObjectGenerator, TextureMap, Vec<Actor>, Vec<Team>),
     Combat(CombatData<'a, 'b>),
 }
 
 pub struct CombatData<'a, 'b> {
     dispatcher: Dispatcher<'a, 'b>,
-    log: Vec<DisplayStr>,
+    log: Vec<LogEvent>,
 
     pub score: u64,
     pub state: CombatState,
@@ -62,9 +107,10 @@ impl<'a, 'b> CombatData<'a, 'b> {
         mut world: World,
         dispatcher: Dispatcher<'a, 'b>,
         teams: Vec<Team>,
+        decks: HashMap<TeamId, Deck>,
     ) -> Self {
         let turn = TurnState::new(&teams);
-        let teams = TeamSet::new(teams);
+        let teams = TeamSet::new(teams, decks);
 
         world.insert(teams);
 
@@ -86,10 +132,94 @@ impl<'a, 'b> CombatData<'a, 'b> {
         &self.state
     }
 
+    /// The latest events of the combat (oldest first)
+    pub fn log(&self) -> &[LogEvent] {
+        &self.log
+    }
+
     pub fn get_world(&self) -> &World {
         &self.world
     }
 
+    /// The team whose decision the game is waiting for (if any)
+    pub fn waiting_for(&self) -> Option<TeamId> {
+        let ctxt = match &self.state {
+            CombatState::WaitForUserInput(ctxt, _) => ctxt,
+            _ => return None,
+        };
+
+        match ctxt {
+            InputContext::ActivateActor { team, .. } | InputContext::HandOver { team } => {
+                Some(*team)
+            }
+
+            InputContext::SelectAction { options, .. } => {
+                let cw = CoreWorld::new(&self.world);
+
+                // either the active actor has to act or the user may choose
+                // which team member to activate next
+                cw.find_actor(|a| a.active).map(|a| a.team).or_else(|| {
+                    options.values().flatten().find_map(|a| match a {
+                        Action::ActivateActor(id) => cw.get_actor(*id).map(|a| a.team),
+                        _ => None,
+                    })
+                })
+            }
+        }
+    }
+
+    /// The remaining activations of all actors in the order in which they
+    /// will happen: the lowest card first, ties are broken the same way as
+    /// when the game looks for the next actor (the active actor is not part
+    /// of it)
+    pub fn initiative_order(&self) -> Vec<(ID, Activation)> {
+        let cw = CoreWorld::new(&self.world);
+        let mut entries = cw
+            .actors()
+            .flat_map(|a| {
+                a.activations
+                    .iter()
+                    .map(move |act| (a.id, a.team, act.clone()))
+            })
+            .collect::<Vec<_>>();
+
+        entries.sort_by(|(id_a, team_a, act_a), (id_b, team_b, act_b)| {
+            act_a
+                .initiativ()
+                .cmp(&act_b.initiativ())
+                .then_with(|| self.turn.cmp_team_by_priority(*team_a, *team_b))
+                .then(id_a.cmp(id_b))
+        });
+
+        entries.into_iter().map(|(id, _, act)| (id, act)).collect()
+    }
+
+    /// Sums up the state of the game (e.g. to check if the peers of a
+    /// networked game are still in sync)
+    pub fn state_hash(&self) -> u64 {
+        let cw = CoreWorld::new(&self.world);
+        let mut actors = cw.actors().collect::<Vec<_>>();
+        let mut hasher = DefaultHasher::new();
+
+        actors.sort_by_key(|a| a.id);
+
+        self.turn.turn_number.hash(&mut hasher);
+        self.score.hash(&mut hasher);
+
+        for a in actors {
+            a.id.hash(&mut hasher);
+            MapPos::from_world_pos(a.pos).hash(&mut hasher);
+            format!("{:?}{:?}", a.health, a.activations).hash(&mut hasher);
+        }
+
+        for td in cw.teams().iter() {
+            td.team.id.hash(&mut hasher);
+            format!("{:?}", td.hand).hash(&mut hasher);
+        }
+
+        hasher.finish()
+    }
+
     pub fn step(mut self, step_result: StepResult) -> Self {
         step_result.unwind(&mut self);
 
@@ -103,12 +233,13 @@ impl<'a, 'b> CombatData<'a, 'b> {
 enum StepChange {
     SwitchState(CombatState),
     AddScore(u64),
-    AppendLog(DisplayStr),
+    AppendLog(Vec<LogEvent>),
     AdvanceGame(TurnState),
     ModifyTeam(TeamData),
     UpdateDeck(TeamId, Deck),
     StartTurn(TeamId, u8),
     RemoveCardFromHand(TeamId, Card),
+    SpotEnemies(TeamId, Vec<ID>),
 }
 
 pub struct StepResult(Option<Vec<StepChange>>);
@@ -131,11 +262,11 @@ impl StepResult {
         self.add_change(StepChange::AddScore(s))
     }
 
-    pub fn append_log(self, l: impl Into<Option<DisplayStr>>) -> Self {
-        if let Some(l) = l.into() {
-            self.add_change(StepChange::AppendLog(l))
-        } else {
+    pub fn append_log(self, l: Vec<LogEvent>) -> Self {
+        if l.is_empty() {
             self
+        } else {
+            self.add_change(StepChange::AppendLog(l))
         }
     }
 
@@ -151,6 +282,14 @@ impl StepResult {
         self.add_change(StepChange::RemoveCardFromHand(team_id, card))
     }
 
+    pub fn spot_enemies(self, team_id: TeamId, enemies: Vec<ID>) -> Self {
+        if enemies.is_empty() {
+            self
+        } else {
+            self.add_change(StepChange::SpotEnemies(team_id, enemies))
+        }
+    }
+
     pub fn update_deck(self, team_id: TeamId, deck: Deck) -> Self {
         self.add_change(StepChange::UpdateDeck(team_id, deck))
     }
@@ -192,6 +331,12 @@ impl StepResult {
                         td.hand.retain(|c| *c != card);
                     }
 
+                    StepChange::SpotEnemies(team_id, enemies) => {
+                        let mut teams_mut = combat_data.world.fetch_mut::<TeamSet>();
+                        let td = teams_mut.get_mut(&team_id);
+                        td.spotted.extend(enemies);
+                    }
+
                     StepChange::UpdateDeck(team_id, deck) => {
                         let mut teams_mut = combat_data.world.fetch_mut::<TeamSet>();
                         let td = teams_mut.get_mut(&team_id);
@@ -199,11 +344,11 @@ impl StepResult {
                     }
 
                     StepChange::AppendLog(l) => {
-                        combat_data.log.insert(0, l);
+                        l.iter().for_each(write_to_log_file);
+                        combat_data.log.extend(l);
 
-                        if combat_data.log.len() > 10 {
-                            combat_data.log.pop();
-                        }
+                        let excess = combat_data.log.len().saturating_sub(MAX_LOG_EVENTS);
+                        combat_data.log.drain(..excess);
                     }
                 }
             }
@@ -222,6 +367,8 @@ pub enum CombatState {
     WaitForUserInput(InputContext, Option<SelectedPos>),
     WaitUntil(Instant, Vec<Action>),
     ResolveAction(Vec<Action>),
+    /// The battle is over; the teams which are left standing have won
+    GameOver(Vec<TeamId>),
 }
 
 #[derive(Debug, Clone)]
@@ -236,34 +383,69 @@ pub struct TeamData {
     pub deck: Deck,
     pub hand: Vec<Card>,
     pub ready: bool,
+    /// The enemies the team has seen so far (the AI ignores everyone else)
+    pub spotted: HashSet<ID>,
+    /// The AI takes over the whole team until the end of the turn
+    pub autopilot: bool,
+    /// Team members which are controlled by the AI (until told otherwise)
+    pub autopilot_actors: HashSet<ID>,
+    /// The AI suggests an action whenever the player has to choose one
+    pub advisor: bool,
+    /// The team has confirmed that it is their turn to plan (hot-seat only)
+    pub handed_over: bool,
+    /// The cards the team has assigned in the current planning phase (latest
+    /// last); they can be taken back until the team is ready
+    pub planned_boosts: Vec<PlannedBoost>,
+}
+
+/// A card assignment and everything needed to take it back
+#[derive(Clone, Debug)]
+pub struct PlannedBoost {
+    pub actor: ID,
+    /// The hand of the team before the card was assigned
+    pub hand: Vec<Card>,
+    /// The activations of the actor before the card was assigned
+    pub activations: Vec<Activation>,
 }
 
 impl TeamData {
-    fn new(team: Team) -> Self {
-        let mut deck = Deck::new_rnd();
-        let hand = if team.is_pc {
-            (1..=3).map(|_| deck.deal()).collect::<Vec<_>>()
-        } else {
-            vec![]
-        };
+    fn new(team: Team, mut deck: Deck) -> Self {
+        let hand = (1..=3).map(|_| deck.deal()).collect::<Vec<_>>();
 
         TeamData {
             team,
             deck,
             hand,
             ready: false,
+            spotted: HashSet::new(),
+            autopilot: false,
+            autopilot_actors: HashSet::new(),
+            advisor: false,
+            handed_over: false,
+            planned_boosts: vec![],
         }
     }
 
+    /// Changes how the team regards the other team
+    pub fn set_relation(&mut self, other: TeamId, relation: Relation) {
+        self.team.relations.retain(|(t, _)| *t != other);
+        self.team.relations.push((other, relation));
+    }
+
+    /// Checks if the AI decides for the given team member
+    pub fn is_autopiloted(&self, id: ID) -> bool {
+        !self.team.is_pc || self.autopilot || self.autopilot_actors.contains(&id)
+    }
+
     pub fn start_new_turn(&mut self, mut num_draws: u8) {
-        if self.team.is_pc {
-            // only player controlled teams should have a hand
-            while self.hand.len() < 6 && num_draws > 0 {
-                self.hand.push(self.deck.deal());
-                num_draws -= 1;
-            }
+        while self.hand.len() < 6 && num_draws > 0 {
+            self.hand.push(self.deck.deal());
+            num_draws -= 1;
         }
         self.ready = false;
+        self.autopilot = false;
+        self.handed_over = false;
+        self.planned_boosts.clear();
     }
 }
 
@@ -271,11 +453,12 @@ impl TeamData {
 pub struct TeamSet(BTreeMap<TeamId, TeamData>);
 
 impl TeamSet {
-    fn new(mut teams: Vec<Team>) -> Self {
+    fn new(mut teams: Vec<Team>, mut decks: HashMap<TeamId, Deck>) -> Self {
         let mut btree_map = BTreeMap::new();
 
         for t in teams.drain(..) {
-            btree_map.insert(t.id, TeamData::new(t));
+            let deck = decks.remove(&t.id).unwrap();
+            btree_map.insert(t.id, TeamData::new(t, deck));
         }
 
         Self(btree_map)
@@ -285,6 +468,42 @@ impl TeamSet {
         self.0.get(team_id).unwrap()
     }
 
+    pub fn iter(&self) -> impl Iterator<Item = &TeamData> {
+        self.0.values()
+    }
+
+    /// Checks if more than one team is controlled by human players on this
+    /// machine
+    pub fn is_hot_seat(&self) -> bool {
+        self.iter()
+            .filter(|td| td.team.is_pc && !td.team.is_remote)
+            .count()
+            > 1
+    }
+
+    /// Looks up how the two teams regard each other (it is enough if one of
+    /// them states the relation)
+    pub fn relation(&self, a: TeamId, b: TeamId) -> Relation {
+        if a == b {
+            return Relation::Allied;
+        }
+
+        let stance = |from: TeamId, to: TeamId| {
+            self.0
+                .get(&from)
+                .and_then(|td| td.team.relations.iter().find(|(t, _)| *t == to))
+                .map(|(_, r)| *r)
+        };
+
+        stance(a, b)
+            .or_else(|| stance(b, a))
+            .unwrap_or(Relation::Hostile)
+    }
+
+    pub fn is_hostile(&self, a: TeamId, b: TeamId) -> bool {
+        self.relation(a, b) == Relation::Hostile
+    }
+
     pub fn get_mut(&mut self, team_id: &TeamId) -> &mut TeamData {
         self.0.get_mut(team_id).unwrap()
     }
@@ -433,6 +652,34 @@ impl TurnState {
             .collect()
     }
 
+    /// Postpones the given reinforcements (which should have arrived in the
+    /// current turn) to the next turn, e.g. because their tile is occupied
+    pub fn delay_reinforcements(mut self, delayed: &[(TeamId, MapPos, ActorTemplateName)]) -> Self {
+        for (team_id, mpos, template) in delayed {
+            let turn_number = self.turn_number;
+            let entry = self.reinforcements.iter_mut().find(|(turn, t, p, tmpl)| {
+                *turn == turn_number && t == team_id && p == mpos && tmpl == template
+            });
+
+            if let Some((turn, ..)) = entry {
+                *turn += 1;
+            }
+        }
+
+        self.next_reinforcements = self.next_reinforcements();
+        self
+    }
+
+    /// The reinforcements which have yet to arrive (and the number of turns
+    /// until they do)
+    pub fn upcoming_reinforcements(&self) -> Vec<(u64, TeamId, MapPos)> {
+        self.reinforcements
+            .iter()
+            .filter(|(turn, ..)| *turn > self.turn_number)
+            .map(|(turn, team_id, mpos, _)| (*turn - self.turn_number, *team_id, *mpos))
+            .collect()
+    }
+
     fn next_reinforcements(&self) -> Option<u64> {
         self.reinforcements
             .iter()
@@ -460,18 +707,30 @@ fn test_stepping_turn_state_activates_teams_correctly() {
             id: TeamId::new(1),
             name: "Team #1",
             is_pc: true,
+            is_remote: false,
+            relations: vec![],
+            deck: crate::core::DeckName::new("deck#standard"),
+            deck_changes: vec![],
             reinforcements: None,
         },
         Team {
             id: TeamId::new(2),
             name: "Team #2",
             is_pc: true,
+            is_remote: false,
+            relations: vec![],
+            deck: crate::core::DeckName::new("deck#standard"),
+            deck_changes: vec![],
             reinforcements: None,
         },
         Team {
             id: TeamId::new(3),
             name: "Team #3",
             is_pc: true,
+            is_remote: false,
+            relations: vec![],
+            deck: crate::core::DeckName::new("deck#standard"),
+            deck_changes: vec![],
             reinforcements: None,
         },
     ];
@@ -576,3 +835,38 @@ fn test_stepping_turn_state_activates_teams_correctly() {
     assert_eq!(turn_state.priority_team_idx, 0);
     assert!(turn_state.get_active_team().is_none());
 }
+
+#[test]
+fn test_teams_are_hostile_unless_stated_otherwise() {
+    let team = |id: u8, relations: Vec<(TeamId, Relation)>| Team {
+        name: "Test",
+        id: TeamId::new(id),
+        is_pc: false,
+        is_remote: false,
+        relations,
+        deck: crate::core::DeckName::new("deck#standard"),
+        deck_changes: vec![],
+        reinforcements: None,
+    };
+    let decks = (1..=3).map(|id| (TeamId::new(id), Deck::new_rnd()));
+
+    let teams = TeamSet::new(
+        vec![
+            team(1, vec![(TeamId::new(2), Relation::Allied)]),
+            team(2, vec![]),
+            team(3, vec![(TeamId::new(1), Relation::Neutral)]),
+        ],
+        decks.collect(),
+    );
+
+    assert_eq!(
+        teams.relation(TeamId::new(2), TeamId::new(1)),
+        Relation::Allied
+    );
+    assert_eq!(
+        teams.relation(TeamId::new(1), TeamId::new(3)),
+        Relation::Neutral
+    );
+    assert!(teams.is_hostile(TeamId::new(2), TeamId::new(3)));
+    assert!(!teams.is_hostile(TeamId::new(3), TeamId::new(3)));
+}
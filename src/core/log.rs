@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter, Result};
+use std::fs::File;
+use std::io::Write;
+
+use crate::core::{Card, Impact};
+
+thread_local! {
+    /// Every log event is also written to this file (if there is one)
+    static LOG_FILE: RefCell<Option<File>> = RefCell::new(None);
+}
+
+/// Writes the combat log to the given file (in addition to the log panel)
+pub fn write_log_to(path: &str) -> std::result::Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+
+    LOG_FILE.with(|f| *f.borrow_mut() = Some(file));
+    Ok(())
+}
+
+/// Appends an event to the log file (if there is one)
+pub fn write_to_log_file(event: &LogEvent) {
+    LOG_FILE.with(|f| {
+        if let Some(file) = f.borrow_mut().as_mut() {
+            // a broken log file should not end the game
+            let _ = writeln!(file, "{}", event);
+        }
+    });
+}
+
+/// Something which happened during the combat (shown in the combat log)
+#[derive(Debug, Clone)]
+pub enum LogEvent {
+    /// An actor attacks someone (or something); the quality of the attack is
+    /// the skill of the attacker plus the value of the effort card
+    Attack {
+        attacker: String,
+        target: String,
+        attack: String,
+        skill: u8,
+        effort: Card,
+        quality: u8,
+    },
+
+    /// Someone flips cards to overcome a challenge
+    Challenge {
+        name: String,
+        kind: ChallengeKind,
+        /// The card which counts and all cards which were drawn
+        draw: (Card, Vec<Card>),
+        skill: u8,
+        target_num: u8,
+        success_lvl: i8,
+    },
+
+    /// What the attack did to its target
+    Impact { target: String, impact: Impact },
+}
+
+#[derive(Debug, Clone)]
+pub enum ChallengeKind {
+    /// The target tries to avoid being hit
+    Defence,
+    /// The attacker tries to get through the armor (soak) of the target which
+    /// is reduced by the rend of the attack
+    Damage { soak: u8, rend: u8 },
+}
+
+impl Display for LogEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            LogEvent::Attack {
+                attacker,
+                target,
+                attack,
+                skill,
+                effort,
+                quality,
+            } => write!(
+                f,
+                "{} attacks {} with {}: quality {} (skill {} + effort {})",
+                attacker, target, attack, quality, skill, effort
+            ),
+
+            LogEvent::Challenge {
+                name,
+                kind,
+                draw: (card, cards),
+                skill,
+                target_num,
+                success_lvl,
+            } => {
+                let what = match kind {
+                    ChallengeKind::Defence => "defends".to_string(),
+                    ChallengeKind::Damage { soak, rend } => {
+                        format!("tries to wound (soak {} - rend {})", soak, rend)
+                    }
+                };
+
+                let drawn = if cards.len() > 1 {
+                    let cards = cards.iter().map(Card::to_string).collect::<Vec<_>>();
+                    format!(" (drawn: {})", cards.join(", "))
+                } else {
+                    String::new()
+                };
+
+                write!(
+                    f,
+                    "{} {}: skill {} + {}{} vs. {} => success level {}",
+                    name, what, skill, card, drawn, target_num, success_lvl
+                )
+            }
+
+            LogEvent::Impact { target, impact } => match impact {
+                Impact::Miss() => write!(f, "{} is missed", target),
+                Impact::Block(..) => write!(f, "{} blocks the hit", target),
+                Impact::Wound(w, _) => write!(
+                    f,
+                    "{} is hit (pain: {}, wounds: {})",
+                    target, w.pain, w.wound
+                ),
+                Impact::Damage(dmg, _) => write!(f, "{} takes {} damage", target, dmg),
+                Impact::ForceMove {
+                    distance, violent, ..
+                } => {
+                    let how = if *violent { "pushed" } else { "pulled" };
+                    write!(f, "{} is {} {} tiles", target, how, distance)
+                }
+            },
+        }
+    }
+}
+
+#[test]
+fn test_log_events_show_the_whole_challenge() {
+    use crate::core::Suite;
+
+    let event = LogEvent::Challenge {
+        name: "Tank".to_string(),
+        kind: ChallengeKind::Damage { soak: 8, rend: 2 },
+        draw: (
+            Card::new(10, Suite::PhysicalStr),
+            vec![
+                Card::new(3, Suite::MentalAg),
+                Card::new(10, Suite::PhysicalStr),
+            ],
+        ),
+        skill: 4,
+        target_num: 6,
+        success_lvl: 2,
+    };
+
+    assert_eq!(
+        event.to_string(),
+        "Tank tries to wound (soak 8 - rend 2): skill 4 + 10 of Clubs \
+         (drawn: 3 of Diamonds, 10 of Clubs) vs. 6 => success level 2"
+    );
+}
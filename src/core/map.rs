@@ -4,12 +4,29 @@ use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
 use std::num::NonZeroU8;
 
+use serde::{Deserialize, Serialize};
+
 use crate::core::model::*;
 
 #[derive(Debug, Clone, Copy)]
 pub enum TileType {
     Floor,
     Void,
+
+    /// Difficult terrain (e.g. rubble or mud) which slows down anyone walking
+    Rough,
+
+    /// Shallow water; cannot be crossed underground
+    Water,
+
+    /// A deep hole; deadly for anyone who cannot fly
+    Pit,
+
+    /// Elevated ground which gives an advantage when attacking from it
+    HighGround,
+
+    /// Dangerous ground (e.g. fire or acid) which hurts anyone stepping on it
+    Hazard,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -249,7 +266,7 @@ pub enum Obstacle {
     Impediment(NonZeroU8, i8),
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct MapPos(pub i32, pub i32);
 
 impl MapPos {
@@ -301,6 +318,11 @@ impl ObstacleSet {
         hm.remove(&pos);
         Self(hm)
     }
+
+    /// Returns the total costs of moving along the given path
+    pub fn path_costs(&self, path: &Path) -> f32 {
+        path.iter().map(|t| costs(t, self)).sum()
+    }
 }
 
 // pub struct LineIter<'a> {
@@ -406,8 +428,9 @@ impl<'a> Iterator for TileIter<'a> {
 
             if self.cur_col < cols.len() {
                 let tt = cols[self.cur_col].clone();
+                let tile = Tile(self.cur_col as u32, self.cur_row as u32, tt);
                 self.cur_col = self.cur_col + 1;
-                return Some(Tile(self.cur_col as u32, self.cur_row as u32, tt));
+                return Some(tile);
             } else {
                 self.cur_row = self.cur_row + 1;
                 self.cur_col = 0;
@@ -454,10 +477,9 @@ impl<'a> Iterator for NeighborTileIter<'a> {
                     continue;
                 }
 
-                if self.obstacles.0.contains_key(&p) {
-                    // if !self.obstacles.0.get(&p).map(|o| o.allow_movement).unwrap_or(true) {
-                    // if let Some(Obstacle::Inaccessible()) = self.obstacles.0.get(&p) {
+                if let Some(Obstacle::Blocker) = self.obstacles.0.get(&p) {
                     // the way is blocked by an inpenetrable obstacle
+                    // (impediments only make it more costly to get there)
                     continue;
                 }
 
@@ -469,6 +491,14 @@ impl<'a> Iterator for NeighborTileIter<'a> {
     }
 }
 
+#[test]
+fn it_iterates_the_tiles_row_by_row() {
+    let m = Map(vec![row(vec![1, 0]), row(vec![0, 1])]);
+    let tiles = m.tiles().map(|t| (t.0, t.1)).collect::<Vec<_>>();
+
+    assert_eq!(tiles, vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+}
+
 #[test]
 fn it_can_find_a_path() {
     let m = Map(vec![
@@ -493,6 +523,32 @@ fn it_can_find_a_path() {
     assert_eq!(p.next(), Some(&Tile(3, 1, TileType::Floor)));
 }
 
+#[test]
+fn it_avoids_costly_terrain() {
+    let m = Map(vec![
+        row(vec![1, 1, 1, 1, 1]),
+        row(vec![1, 1, 1, 1, 1]),
+        row(vec![1, 1, 1, 1, 1]),
+    ]);
+
+    let rough = Obstacle::Impediment(NonZeroU8::new(3).unwrap(), 0);
+    let obstacles = ObstacleSet(HashMap::from_iter(vec![
+        (MapPos(1, 1), rough),
+        (MapPos(2, 1), rough),
+        (MapPos(3, 1), rough),
+    ]));
+
+    let from = MapPos(0, 1);
+    let to = MapPos(4, 1);
+    let p = m.find_path(from, to, &obstacles).unwrap();
+
+    // the straight path crosses the rough terrain
+    // => it is cheaper to walk around it
+    assert_eq!(m.find_straight_path(from, to, &obstacles), None);
+    assert_eq!(p.len(), 4);
+    assert_eq!(obstacles.path_costs(&p), 4.0);
+}
+
 #[test]
 fn it_can_find_a_staight_path() {
     let m = Map(vec![
@@ -519,27 +575,30 @@ pub fn dummy() -> Map {
     Map(vec![
         row(vec![0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0]),
         row(vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0]),
-        row(vec![0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]),
-        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0]),
-        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0]),
-        row(vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]),
+        row(vec![0, 0, 1, 1, 2, 2, 1, 1, 1, 1, 1, 0, 0]),
+        row(vec![0, 1, 1, 2, 2, 1, 1, 1, 1, 1, 1, 1, 0]),
+        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 5, 1, 1, 0]),
+        row(vec![1, 1, 1, 1, 1, 1, 1, 1, 5, 5, 1, 1, 1]),
         row(vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]),
         row(vec![1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1]),
-        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0]),
-        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0]),
-        row(vec![0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0]),
-        row(vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0]),
+        row(vec![0, 1, 1, 4, 1, 1, 1, 1, 1, 1, 1, 1, 0]),
+        row(vec![0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 6, 1, 0]),
+        row(vec![0, 0, 1, 1, 1, 3, 3, 3, 1, 1, 1, 0, 0]),
+        row(vec![0, 0, 0, 1, 1, 1, 3, 1, 1, 1, 0, 0, 0]),
         row(vec![0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0, 0]),
     ])
 }
 
+/// 0 => Void, 1 => Floor, 2 => Rough, 3 => Water, 4 => Pit, 5 => HighGround, 6 => Hazard
 fn row(row_tiles: Vec<u8>) -> Vec<TileType> {
-    Vec::from_iter(row_tiles.iter().map(|&i| {
-        if i > 0 {
-            TileType::Floor
-        } else {
-            TileType::Void
-        }
+    Vec::from_iter(row_tiles.iter().map(|&i| match i {
+        0 => TileType::Void,
+        2 => TileType::Rough,
+        3 => TileType::Water,
+        4 => TileType::Pit,
+        5 => TileType::HighGround,
+        6 => TileType::Hazard,
+        _ => TileType::Floor,
     }))
 }
 
@@ -4,23 +4,32 @@ mod ai;
 mod cards;
 // mod dice;
 mod flow;
+mod log;
 mod map;
 mod model;
+mod rng;
+mod structure;
 mod text;
+mod visibility;
 mod visuals;
 mod world;
 
 pub use action::*;
 // pub use ai::AttackVector;
+pub use ai::find_charge_path;
 pub use cards::*;
 // pub use dice::D6;
 pub use flow::{
-    step, CombatData, CombatPhase, CombatState, Game, InputContext, SelectedPos, TurnState,
-    UserInput,
+    network_game, simulate, step, CombatData, CombatPhase, CombatState, Game, Headless,
+    InputContext, SelectedPos, SimulationOutcome, TurnState, UserInput,
 };
+pub use log::*;
 pub use map::*;
 pub use model::*;
+pub use rng::*;
+pub use structure::*;
 pub use text::DisplayStr;
+pub use visibility::*;
 pub use visuals::*;
 pub use world::*;
 
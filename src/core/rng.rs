@@ -0,0 +1,32 @@
+use std::cell::RefCell;
+
+use rand::prelude::*;
+
+thread_local! {
+    /// The source of all random numbers which influence the game (but not its
+    /// presentation); the same seed leads to the same game given the same
+    /// user input (see lockstep networking)
+    static GAME_RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// Restarts the random numbers of the game from the given seed
+pub fn seed_rng(seed: u64) {
+    GAME_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Provides the random number generator of the game
+pub fn with_rng<T, F>(f: F) -> T
+where
+    F: FnOnce(&mut StdRng) -> T,
+{
+    GAME_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+#[test]
+fn test_same_seed_same_numbers() {
+    seed_rng(42);
+    let first = with_rng(|rng| rng.gen::<u64>());
+
+    seed_rng(42);
+    assert_eq!(with_rng(|rng| rng.gen::<u64>()), first);
+}
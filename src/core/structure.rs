@@ -0,0 +1,112 @@
+use std::num::NonZeroU8;
+
+use crate::components::{Hitbox, ObstacleCmp};
+
+use super::{Obstacle, WorldPos, ID};
+
+/// A destructible part of the arena (e.g. a wall) which can be used as cover
+/// and which will crumble under enough attacks
+#[derive(Debug, Clone)]
+pub struct Structure {
+    pub id: ID,
+    pub name: String,
+    pub pos: WorldPos,
+    pub integrity: u8,
+    pub max_integrity: u8,
+    pub armor: u8,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StructureState {
+    /// Blocks movement and line of sight completely
+    Intact,
+    /// Can be climbed over and provides some cover
+    Damaged,
+    /// Nothing left but a heap of debris which slows down movement
+    Rubble,
+}
+
+impl Structure {
+    pub fn new_wall(pos: WorldPos) -> Self {
+        Self {
+            id: ID::new(),
+            name: "Wall".to_string(),
+            pos,
+            integrity: 6,
+            max_integrity: 6,
+            armor: 8,
+        }
+    }
+
+    pub fn state(&self) -> StructureState {
+        if self.integrity == 0 {
+            StructureState::Rubble
+        } else if 2 * self.integrity > self.max_integrity {
+            StructureState::Intact
+        } else {
+            StructureState::Damaged
+        }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.state() == StructureState::Rubble
+    }
+
+    pub fn damage(mut self, dmg: u8) -> Self {
+        self.integrity = self.integrity.saturating_sub(dmg);
+        self
+    }
+
+    /// Returns how the structure hinders movement and attacks in its current state
+    pub fn obstacle(&self) -> ObstacleCmp {
+        match self.state() {
+            StructureState::Intact => ObstacleCmp {
+                movement: (Some(Obstacle::Blocker), Some(Obstacle::Blocker), None),
+                reach: Some(Hitbox::new_pillar()),
+            },
+
+            StructureState::Damaged => ObstacleCmp {
+                movement: (
+                    Some(Obstacle::Impediment(NonZeroU8::new(3).unwrap(), 0)),
+                    None,
+                    None,
+                ),
+                reach: Some(Hitbox::new_low_cover()),
+            },
+
+            StructureState::Rubble => ObstacleCmp {
+                movement: (
+                    Some(Obstacle::Impediment(NonZeroU8::new(2).unwrap(), 0)),
+                    None,
+                    None,
+                ),
+                reach: None,
+            },
+        }
+    }
+
+    pub fn visual(&self) -> &'static str {
+        match self.state() {
+            StructureState::Intact => "wall-1",
+            StructureState::Damaged => "wall-1-damaged",
+            StructureState::Rubble => "wall-1-rubble",
+        }
+    }
+}
+
+#[test]
+fn test_walls_degrade_to_rubble() {
+    let wall = Structure::new_wall(WorldPos::new(1.0, 1.0, 0.0));
+    assert_eq!(wall.state(), StructureState::Intact);
+
+    let wall = wall.damage(3);
+    assert_eq!(wall.state(), StructureState::Damaged);
+    assert!(matches!(
+        wall.obstacle().movement.0,
+        Some(Obstacle::Impediment(..))
+    ));
+
+    let wall = wall.damage(5);
+    assert_eq!(wall.state(), StructureState::Rubble);
+    assert!(wall.obstacle().reach.is_none());
+}
@@ -0,0 +1,140 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::components::ObstacleCmp;
+
+use super::{Actor, CoreWorld, MapPos, Obstacle, SuperLineIter, TeamId, ID};
+
+/// The positions a team can currently see
+#[derive(Debug, Clone)]
+pub struct Vision {
+    team: TeamId,
+    visible: HashSet<MapPos>,
+    observers: Vec<MapPos>,
+}
+
+impl Vision {
+    pub fn is_visible(&self, p: MapPos) -> bool {
+        self.visible.contains(&p)
+    }
+
+    /// An actor is visible if its position can be seen; actors who hide
+    /// underground can only be spotted from right next to them (a team
+    /// always knows where its own members are)
+    pub fn can_see_actor(&self, a: &Actor) -> bool {
+        let p = MapPos::from_world_pos(a.pos);
+
+        if a.team == self.team {
+            return true;
+        }
+
+        if a.is_underground() {
+            return self.observers.iter().any(|o| o.distance(p) <= 1);
+        }
+
+        self.is_visible(p)
+    }
+}
+
+/// Computes what the members of the given team can see (combined)
+pub fn team_vision(team: TeamId, world: &CoreWorld) -> Vision {
+    let obstacles = world.collect_obstacles();
+    let observers = world
+        .actors()
+        .filter(|a| a.team == team && a.is_concious())
+        .map(|a| MapPos::from_world_pos(a.pos))
+        .collect::<Vec<_>>();
+
+    let mut visible = HashSet::new();
+
+    for t in world.map().tiles() {
+        let p = t.to_map_pos();
+
+        if observers
+            .iter()
+            .any(|o| has_line_of_sight(*o, p, &obstacles))
+        {
+            visible.insert(p);
+        }
+    }
+
+    Vision {
+        team,
+        visible,
+        observers,
+    }
+}
+
+/// Checks if there is anything blocking the view between the two positions;
+/// only obstacles which are in between count (you can always see the wall
+/// in front of you)
+pub fn has_line_of_sight(
+    from: MapPos,
+    to: MapPos,
+    obstacles: &HashMap<MapPos, (ObstacleCmp, Option<ID>)>,
+) -> bool {
+    if from == to {
+        return true;
+    }
+
+    let p0 = from.to_world_pos().as_xy();
+    let p1 = to.to_world_pos().as_xy();
+
+    for p in SuperLineIter::new(from, to).take_while(|p| *p != to) {
+        if p == from {
+            continue;
+        }
+
+        let blocked = obstacles
+            .get(&p)
+            .and_then(|(obs, _)| obs.reach.as_ref())
+            .and_then(|hitbox| hitbox.obstacle_at(p.to_world_pos().as_xy(), p0, p1));
+
+        if let Some(Obstacle::Blocker) = blocked {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[test]
+fn test_a_team_always_sees_its_own_members() {
+    use super::{ActorTemplateName, ObjectGenerator};
+
+    let generator = ObjectGenerator::new(std::path::Path::new("assets/data/"));
+    let team = TeamId::new(0);
+    let pos = MapPos(3, 3);
+    let actor = generator.generate_player(
+        pos.to_world_pos(),
+        team,
+        ActorTemplateName::new("actor#tank"),
+    );
+    let enemy = generator.generate_player(
+        pos.to_world_pos(),
+        TeamId::new(1),
+        ActorTemplateName::new("actor#tank"),
+    );
+    let vision = Vision {
+        team,
+        visible: HashSet::new(),
+        observers: vec![],
+    };
+
+    assert!(vision.can_see_actor(&actor));
+    assert!(!vision.can_see_actor(&enemy));
+}
+
+#[test]
+fn test_walls_block_the_line_of_sight() {
+    use crate::components::Hitbox;
+
+    let wall = ObstacleCmp {
+        movement: (Some(Obstacle::Blocker), Some(Obstacle::Blocker), None),
+        reach: Some(Hitbox::new_pillar()),
+    };
+    let obstacles = HashMap::from([(MapPos(1, 0), (wall, None))]);
+
+    assert!(!has_line_of_sight(MapPos(0, 0), MapPos(2, 0), &obstacles));
+    assert!(has_line_of_sight(MapPos(0, 0), MapPos(1, 0), &obstacles));
+    assert!(has_line_of_sight(MapPos(0, 0), MapPos(0, 2), &obstacles));
+}
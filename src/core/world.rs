@@ -4,10 +4,10 @@ use specs::join::JoinIter;
 use specs::prelude::*;
 use specs::World as SpecsWorld;
 
-use crate::components::{ActorCmp, ObstacleCmp, Position};
+use crate::components::{ActorCmp, ObstacleCmp, Position, StructureCmp};
 
 use super::{
-    flow::TeamSet, Actor, ActorTemplateName, Deck, Map, MapPos, ObjectGenerator, TeamId,
+    flow::TeamSet, Actor, ActorTemplateName, Deck, Map, MapPos, ObjectGenerator, Structure, TeamId,
     TraitStorage, ID,
 };
 
@@ -29,18 +29,21 @@ pub struct CoreWorld<'a> {
 
     // component storages
     actors: ReadStorage<'a, ActorCmp>,
+    structures: ReadStorage<'a, StructureCmp>,
 
     entity_map: HashMap<ID, Entity>,
     updates: HashMap<ID, Option<Actor>>,
+    structure_updates: HashMap<ID, Structure>,
 }
 
 impl<'a> CoreWorld<'a> {
     pub fn new(w: &'a SpecsWorld) -> Self {
         let mut entity_map = HashMap::new();
-        let (generator, entities, actors): (
+        let (generator, entities, actors, structures): (
             Read<ObjectGenerator>,
             Entities,
             ReadStorage<ActorCmp>,
+            ReadStorage<StructureCmp>,
         ) = w.system_data();
 
         for (e, ActorCmp(a)) in (&entities, &actors).join() {
@@ -55,8 +58,10 @@ impl<'a> CoreWorld<'a> {
             decks_changed: false,
             generator,
             actors,
+            structures,
             entity_map,
             updates: HashMap::new(),
+            structure_updates: HashMap::new(),
         }
     }
 
@@ -86,7 +91,12 @@ impl<'a> CoreWorld<'a> {
         ) = self.world.system_data();
 
         for (e, o, p) in (&entities, &obstacles, &positions).join() {
-            if let Some(ActorCmp(a)) = self.actors.get(e) {
+            if let Some(StructureCmp(s)) = self.structures.get(e) {
+                // the obstacle is a structure which may have been damaged
+                // -> derive the obstacle from its current state
+                let s = self.structure_updates.get(&s.id).unwrap_or(s);
+                result.insert(MapPos::from_world_pos(p.0), (s.obstacle(), Some(s.id)));
+            } else if let Some(ActorCmp(a)) = self.actors.get(e) {
                 // obstacle is a game object which may have been changed
                 // -> check for updates
                 if self.updates.contains_key(&a.id) {
@@ -165,6 +175,28 @@ impl<'a> CoreWorld<'a> {
         None
     }
 
+    pub fn get_structure(&self, id: ID) -> Option<&Structure> {
+        if let Some(s) = self.structure_updates.get(&id) {
+            return Some(s);
+        }
+
+        (&self.structures)
+            .join()
+            .map(|StructureCmp(s)| s)
+            .find(|s| s.id == id)
+    }
+
+    pub fn structures(&self) -> Vec<Structure> {
+        (&self.structures)
+            .join()
+            .map(|StructureCmp(s)| self.structure_updates.get(&s.id).unwrap_or(s).clone())
+            .collect()
+    }
+
+    pub fn update_structure(&mut self, s: Structure) {
+        self.structure_updates.insert(s.id, s);
+    }
+
     pub fn remove(&mut self, id: ID) {
         self.updates.insert(id, None);
     }
@@ -187,7 +219,13 @@ impl<'a> CoreWorld<'a> {
             .generate_enemy(pos.to_world_pos(), team, template)
     }
 
-    pub fn into_changes(mut self) -> (Option<HashMap<TeamId, Deck>>, Vec<(ID, Option<Actor>)>) {
+    pub fn into_changes(
+        mut self,
+    ) -> (
+        Option<HashMap<TeamId, Deck>>,
+        Vec<(ID, Option<Actor>)>,
+        Vec<Structure>,
+    ) {
         let decks = if self.decks_changed {
             Some(self.decks)
         } else {
@@ -195,8 +233,9 @@ impl<'a> CoreWorld<'a> {
         };
 
         let updates = self.updates.drain().collect::<Vec<_>>();
+        let structure_updates = self.structure_updates.drain().map(|(_, s)| s).collect();
 
-        (decks, updates)
+        (decks, updates, structure_updates)
         // let updates = self.updates.values().drain(..).collect::<Vec<_>>();
     }
 
@@ -1,37 +1,72 @@
 mod components;
 mod core;
+mod net;
 mod ui;
 
 extern crate sdl2;
 
 use std::path::Path;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use sdl2::image::InitFlag;
+use sdl2::render::WindowCanvas;
+use sdl2::sys::SDL_RendererFlags;
+use sdl2::video::FullscreenType;
+use sdl2::VideoSubsystem;
 
-use crate::core::{step, Game, ObjectGenerator, UserInput};
-use crate::ui::{init_ui, poll, render, step_ui, AssetRepo, FontFace};
+use crate::core::{
+    network_game, seed_rng, simulate, step, write_log_to, AiBehaviour, Game, ObjectGenerator,
+    SimulationOutcome, UserInput,
+};
+use crate::net::Session;
+use crate::ui::{
+    init_ui, poll, render, render_to_png, step_ui, AssetRepo, FontFace, KeyBindings, VideoSettings,
+    RESOLUTIONS, UI,
+};
 
 fn main() -> Result<(), String> {
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if let Some(num_games) = arg_value(&args, "--simulate") {
+        // e.g. --simulate 100 --ai "Berserker"
+        let num_games = num_games.parse::<u32>().map_err(|e| e.to_string())?;
+        let ai = arg_value(&args, "--ai").unwrap_or("Tactician(temperature: 0.1)");
+        let ai = ron::from_str::<AiBehaviour>(ai).map_err(|e| e.to_string())?;
+
+        run_simulations(num_games, ai);
+        return Ok(());
+    }
+
+    // e.g. --log-file combat.log
+    if let Some(path) = arg_value(&args, "--log-file") {
+        write_log_to(path)?;
+    }
+
+    // e.g. --host 127.0.0.1:7878 or --join 127.0.0.1:7878
+    let network = if let Some(addr) = arg_value(&args, "--host") {
+        Some((Session::host(addr)?, true))
+    } else if let Some(addr) = arg_value(&args, "--join") {
+        Some((Session::join(addr)?, false))
+    } else {
+        None
+    };
+
+    let settings_path = Path::new("./settings.ron");
+    let settings = VideoSettings::load(settings_path)?;
+
+    // e.g. --renderer software or --renderer opengles2,software
+    let renderers = match arg_value(&args, "--renderer") {
+        Some(names) => names.split(',').map(|n| n.trim().to_string()).collect(),
+        None => settings.renderers.clone(),
+    };
+
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
     let _image_context = sdl2::image::init(InitFlag::PNG);
-    let window = video_subsystem
-        .window("ArenaRL", 1200, 600)
-        // .fullscreen_desktop()
-        .allow_highdpi()
-        .opengl()
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    let pixel_ratio = (window.drawable_size().0 / window.size().0) as u8;
-    let mut canvas = window
-        .into_canvas()
-        .index(find_render_driver("opengl").unwrap())
-        .accelerated()
-        .present_vsync() // caps fps at 60 (monitor refresh rate)
-        .build()
-        .map_err(|e| e.to_string())?;
+    let (mut canvas, pixel_ratio) = create_canvas(&video_subsystem, &settings, &renderers)?;
+    let has_vsync = canvas.info().flags & SDL_RendererFlags::SDL_RENDERER_PRESENTVSYNC as u32 != 0;
+    let frame_time = Duration::from_secs(1) / settings.max_fps.max(1);
+    let mut last_frame = Instant::now();
 
     let texture_creator = canvas.texture_creator();
     let mut assets = AssetRepo::new(&texture_creator).init(
@@ -45,21 +80,58 @@ fn main() -> Result<(), String> {
 
     let mut click_areas = vec![];
     let mut sdl_events = sdl_context.event_pump()?;
-    let mut game = Game::Start(object_generator, texture_map);
-    let mut ui = init_ui((vp.x(), vp.y(), vp.width(), vp.height()), pixel_ratio);
+    let mut game = match &network {
+        Some(((_, seed), is_host)) => {
+            seed_rng(*seed);
+            network_game(object_generator, texture_map, *is_host)
+        }
+        None => Game::Start(object_generator, texture_map),
+    };
+    let mut session = network.map(|((s, _), _)| s);
+    // e.g. --keys my_keys.ron
+    let keys = arg_value(&args, "--keys").unwrap_or("./assets/keys.ron");
+    let keys = KeyBindings::from_file(Path::new(keys))?;
+    let mut ui = init_ui(
+        (vp.x(), vp.y(), vp.width(), vp.height()),
+        pixel_ratio,
+        keys,
+        settings,
+    );
 
     'main: loop {
-        let user_input = poll(&mut sdl_events, &click_areas, &ui);
+        let user_input = poll(&mut sdl_events, &click_areas, &ui, &game);
 
         if let Some(UserInput::Exit()) = user_input {
             break 'main;
         }
 
+        if let Some(UserInput::Screenshot) = user_input {
+            take_screenshot(&ui, &game);
+        }
+
+        let user_input = match session.as_mut() {
+            Some(s) => s.exchange(&game, user_input)?,
+            None => user_input,
+        };
+
+        let settings = ui.settings.clone();
+
         game = step(game, &user_input);
         ui = step_ui(ui, &game, &user_input);
+
+        if ui.settings != settings {
+            apply_video_settings(&mut canvas, &ui.settings)?;
+            ui.settings.save(settings_path)?;
+        }
         click_areas = render(&mut canvas, &ui, &game, &mut assets)?;
 
-        std::thread::sleep(Duration::from_nanos(0)); // TODO: fps limit without vsync
+        if !has_vsync {
+            // vsync caps the fps otherwise
+            if let Some(rest) = frame_time.checked_sub(last_frame.elapsed()) {
+                std::thread::sleep(rest);
+            }
+        }
+        last_frame = Instant::now();
     }
 
     Ok(())
@@ -68,6 +140,138 @@ fn main() -> Result<(), String> {
 //////////////////////////////////////////////////
 // PRIVATE HELPER FUNCTIONS
 
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let idx = args.iter().position(|a| a == name)?;
+    args.get(idx + 1).map(|v| v.as_str())
+}
+
+/// Lets the AI play a number of combats and prints how well it did
+fn run_simulations(num_games: u32, ai: AiBehaviour) {
+    let mut victories = 0;
+    let mut total_turns = 0;
+    let mut total_survivors = 0;
+
+    for i in 1..=num_games {
+        let generator = ObjectGenerator::new(Path::new("assets/data/"));
+        let result = simulate(generator, ai.clone());
+
+        println!(
+            "#{}: {:?} after {} turns ({} survivors, score: {})",
+            i, result.outcome, result.turns, result.survivors, result.score
+        );
+
+        if result.outcome == SimulationOutcome::Victory {
+            victories += 1;
+        }
+        total_turns += result.turns;
+        total_survivors += result.survivors;
+    }
+
+    let n = num_games.max(1) as f32;
+    println!(
+        "{:?}: won {}/{} ({:.0}%), avg. turns: {:.1}, avg. survivors: {:.1}",
+        ai,
+        victories,
+        num_games,
+        100.0 * victories as f32 / n,
+        total_turns as f32 / n,
+        total_survivors as f32 / n,
+    );
+}
+
+/// Saves the current screen as screenshot-<seconds since 1970>.png
+fn take_screenshot(ui: &UI, game: &Game) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = format!("screenshot-{}.png", secs);
+
+    match render_to_png(ui, game, Path::new(&path)) {
+        Ok(()) => println!("Saved {}", path),
+        Err(e) => println!("Could not save a screenshot: {}", e),
+    }
+}
+
+/// Changes the window (the new size is reported as window event)
+fn apply_video_settings(canvas: &mut WindowCanvas, settings: &VideoSettings) -> Result<(), String> {
+    let window = canvas.window_mut();
+    let (width, height) = settings.resolution;
+
+    if settings.fullscreen {
+        window.set_fullscreen(FullscreenType::Desktop)
+    } else {
+        window.set_fullscreen(FullscreenType::Off)?;
+        window.set_size(width, height).map_err(|e| e.to_string())
+    }
+}
+
+/// Creates the window with the first of the given render drivers which works
+/// and returns it together with its pixel ratio (see high dpi)
+fn create_canvas(
+    video_subsystem: &VideoSubsystem,
+    settings: &VideoSettings,
+    renderers: &[String],
+) -> Result<(WindowCanvas, u8), String> {
+    let (width, height) = settings.resolution;
+    let mut errors = vec![];
+
+    for name in renderers {
+        let index = match find_render_driver(name) {
+            Some(index) => index,
+            None => {
+                errors.push(format!("{}: not available", name));
+                continue;
+            }
+        };
+
+        let mut window_builder = video_subsystem.window("ArenaRL", width, height);
+        window_builder.allow_highdpi().resizable();
+
+        if name.starts_with("opengl") {
+            window_builder.opengl();
+        }
+
+        if settings.fullscreen {
+            window_builder.fullscreen_desktop();
+        }
+
+        let mut window = match window_builder.build() {
+            Ok(window) => window,
+            Err(e) => {
+                errors.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        // (the screens are not made for anything smaller)
+        window
+            .set_minimum_size(RESOLUTIONS[0].0, RESOLUTIONS[0].1)
+            .map_err(|e| e.to_string())?;
+
+        let pixel_ratio = (window.drawable_size().0 / window.size().0) as u8;
+        let mut canvas_builder = window.into_canvas().index(index);
+
+        if name != "software" {
+            canvas_builder = canvas_builder.accelerated();
+        }
+
+        if settings.vsync {
+            canvas_builder = canvas_builder.present_vsync(); // caps fps at the monitor refresh rate
+        }
+
+        match canvas_builder.build() {
+            Ok(canvas) => return Ok((canvas, pixel_ratio)),
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    Err(format!(
+        "None of the renderers works ({})",
+        errors.join(", ")
+    ))
+}
+
 /// opengl, opengles2, metal, software, ...
 fn find_render_driver(name: &str) -> Option<u32> {
     for (index, item) in sdl2::render::drivers().enumerate() {
@@ -0,0 +1,422 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    Action, Card, CombatData, CombatState, CoreWorld, Game, InputContext, MapPos, TeamId,
+    UserInput, ID,
+};
+
+/// Lockstep multiplayer: both peers play the same game (with the same seed)
+/// and only exchange the decisions of their own team
+pub struct Session {
+    stream: TcpStream,
+    received: Vec<u8>,
+    /// Bytes which could not be sent yet (without waiting for the other peer)
+    unsent: Vec<u8>,
+    remote_inputs: VecDeque<NetInput>,
+    turn: u64,
+    local_hashes: BTreeMap<u64, u64>,
+    remote_hashes: BTreeMap<u64, u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Message {
+    Hello { seed: u64 },
+    Input(NetInput),
+    StateHash { turn: u64, hash: u64 },
+}
+
+/// The user inputs which change the game (everything else only affects
+/// what the local player sees)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum NetInput {
+    BoostActivation(ID, TeamId, Card),
+    AssigneActivationDone(TeamId),
+    UndoBoost(TeamId),
+    /// The position and the index of the selected action (see InputContext::SelectAction)
+    SelectPlayerAction(MapPos, usize),
+    AutoResolveTurn(TeamId),
+    ToggleAutopilot(TeamId, ID),
+    ToggleAdvisor(TeamId),
+}
+
+impl Session {
+    /// Waits for another player to join and tells them the seed of the game
+    pub fn host(addr: &str) -> Result<(Self, u64), String> {
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+
+        println!("Waiting for another player on {} ...", addr);
+
+        let (stream, peer) = listener.accept().map_err(|e| e.to_string())?;
+        let seed = rand::random();
+        let mut session = Self::new(stream)?;
+
+        println!("{} has joined the game", peer);
+
+        session.send(&Message::Hello { seed })?;
+        Ok((session, seed))
+    }
+
+    /// Connects to a hosted game and waits for its seed
+    pub fn join(addr: &str) -> Result<(Self, u64), String> {
+        let stream = TcpStream::connect(addr).map_err(|e| e.to_string())?;
+        let mut session = Self::new(stream)?;
+
+        loop {
+            match session.receive()? {
+                Some(Message::Hello { seed }) => return Ok((session, seed)),
+                Some(msg) => return Err(format!("Unexpected message: {:?}", msg)),
+                None => std::thread::sleep(std::time::Duration::from_millis(10)),
+            }
+        }
+    }
+
+    fn new(stream: TcpStream) -> Result<Self, String> {
+        stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            stream,
+            received: vec![],
+            unsent: vec![],
+            remote_inputs: VecDeque::new(),
+            turn: 0,
+            local_hashes: BTreeMap::new(),
+            remote_hashes: BTreeMap::new(),
+        })
+    }
+
+    /// Sends the decisions of the local team to the other peer and replaces
+    /// the user input with the decisions of the remote team when it is their
+    /// turn; fails if the game is out of sync
+    pub fn exchange(
+        &mut self,
+        game: &Game,
+        i: Option<UserInput>,
+    ) -> Result<Option<UserInput>, String> {
+        self.flush()?;
+
+        while let Some(msg) = self.receive()? {
+            match msg {
+                Message::Input(input) => self.remote_inputs.push_back(input),
+                Message::StateHash { turn, hash } => {
+                    self.remote_hashes.insert(turn, hash);
+                }
+                Message::Hello { .. } => {}
+            }
+        }
+
+        let data = match game {
+            Game::Combat(data) => data,
+            _ => return Ok(i),
+        };
+
+        self.check_sync(data)?;
+
+        let waiting_for = data.waiting_for();
+        let cw = CoreWorld::new(&data.world);
+        let is_remote = waiting_for
+            .map(|t| cw.teams().get(&t).team.is_remote)
+            .unwrap_or(false);
+
+        if let Some(input) = i.as_ref().and_then(encode) {
+            if waiting_for.is_none() || is_remote {
+                // it is not our turn
+                return Ok(None);
+            }
+
+            self.send(&Message::Input(input))?;
+            return Ok(i);
+        }
+
+        // (moving the mouse must not hold up the decisions of the remote team)
+        let is_idle = matches!(i, None | Some(UserInput::Hover(..)));
+
+        if is_idle && is_remote {
+            if let Some(input) = self.remote_inputs.pop_front() {
+                return decode(input, data).map(Some);
+            }
+        }
+
+        Ok(i)
+    }
+
+    /// Sends a summary of the game at the start of every turn and compares
+    /// it to the one of the other peer
+    fn check_sync(&mut self, data: &CombatData) -> Result<(), String> {
+        if data.turn.turn_number > self.turn {
+            let turn = data.turn.turn_number;
+            let hash = data.state_hash();
+
+            self.turn = turn;
+            self.local_hashes.insert(turn, hash);
+            self.send(&Message::StateHash { turn, hash })?;
+        }
+
+        for (turn, remote_hash) in self.remote_hashes.iter() {
+            if let Some(local_hash) = self.local_hashes.get(turn) {
+                if local_hash != remote_hash {
+                    return Err(format!("The game is out of sync (turn {})", turn));
+                }
+            }
+        }
+
+        let local_hashes = &self.local_hashes;
+        self.remote_hashes
+            .retain(|t, _| !local_hashes.contains_key(t));
+
+        Ok(())
+    }
+
+    fn send(&mut self, msg: &Message) -> Result<(), String> {
+        let line = ron::to_string(msg).map_err(|e| e.to_string())? + "\n";
+
+        self.unsent.extend_from_slice(line.as_bytes());
+        self.flush()
+    }
+
+    /// Sends as much as possible without blocking; the rest is sent with the
+    /// next exchange (so a peer who stops reading cannot freeze the game)
+    fn flush(&mut self) -> Result<(), String> {
+        while !self.unsent.is_empty() {
+            match self.stream.write(&self.unsent) {
+                Ok(0) => return Err("The other player has left the game".to_string()),
+                Ok(n) => {
+                    self.unsent.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the next complete message (if there is one)
+    fn receive(&mut self) -> Result<Option<Message>, String> {
+        let mut buf = [0; 1024];
+
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err("The other player has left the game".to_string()),
+                Ok(n) => self.received.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+
+        match self.received.iter().position(|b| *b == b'\n') {
+            Some(idx) => {
+                let line = self.received.drain(..=idx).collect::<Vec<_>>();
+                let line = String::from_utf8_lossy(&line);
+
+                ron::from_str(line.trim())
+                    .map(Some)
+                    .map_err(|e| e.to_string())
+            }
+
+            None => Ok(None),
+        }
+    }
+}
+
+fn encode(i: &UserInput) -> Option<NetInput> {
+    match i {
+        UserInput::BoostActivation(id, team, card) => {
+            Some(NetInput::BoostActivation(*id, *team, *card))
+        }
+        UserInput::AssigneActivationDone(team) => Some(NetInput::AssigneActivationDone(*team)),
+        UserInput::UndoBoost(team) => Some(NetInput::UndoBoost(*team)),
+        UserInput::AutoResolveTurn(team) => Some(NetInput::AutoResolveTurn(*team)),
+        UserInput::ToggleAutopilot(team, id) => Some(NetInput::ToggleAutopilot(*team, *id)),
+        UserInput::ToggleAdvisor(team) => Some(NetInput::ToggleAdvisor(*team)),
+        UserInput::SelectPlayerAction(p, idx) => Some(NetInput::SelectPlayerAction(*p, *idx)),
+        _ => None,
+    }
+}
+
+fn decode(i: NetInput, data: &CombatData) -> Result<UserInput, String> {
+    Ok(match i {
+        NetInput::BoostActivation(id, team, card) => UserInput::BoostActivation(id, team, card),
+        NetInput::AssigneActivationDone(team) => UserInput::AssigneActivationDone(team),
+        NetInput::UndoBoost(team) => UserInput::UndoBoost(team),
+        NetInput::AutoResolveTurn(team) => UserInput::AutoResolveTurn(team),
+        NetInput::ToggleAutopilot(team, id) => UserInput::ToggleAutopilot(team, id),
+        NetInput::ToggleAdvisor(team) => UserInput::ToggleAdvisor(team),
+        NetInput::SelectPlayerAction(p, idx) => {
+            action_options(data)
+                .and_then(|options| options.get(&p))
+                .and_then(|actions| actions.get(idx))
+                .ok_or(format!("The game is out of sync (no action at {:?})", p))?;
+
+            UserInput::SelectPlayerAction(p, idx)
+        }
+    })
+}
+
+fn action_options<'a>(data: &'a CombatData) -> Option<&'a HashMap<MapPos, Vec<Action>>> {
+    match &data.state {
+        CombatState::WaitForUserInput(InputContext::SelectAction { options, .. }, _) => {
+            Some(options)
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn test_messages_arrive_in_order() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = TcpStream::connect(addr).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    let mut host = Session::new(server).unwrap();
+    let mut peer = Session::new(client).unwrap();
+
+    host.send(&Message::Hello { seed: 42 }).unwrap();
+    host.send(&Message::Input(NetInput::AssigneActivationDone(
+        TeamId::new(1),
+    )))
+    .unwrap();
+
+    let mut received = vec![];
+    while received.len() < 2 {
+        if let Some(msg) = peer.receive().unwrap() {
+            received.push(msg);
+        }
+    }
+
+    assert!(matches!(received[0], Message::Hello { seed: 42 }));
+    assert!(matches!(
+        received[1],
+        Message::Input(NetInput::AssigneActivationDone(_))
+    ));
+}
+
+#[test]
+fn test_peers_stay_in_sync() {
+    use crate::core::{network_game, seed_rng, step, Headless, ObjectGenerator, TextureMap};
+    use std::path::Path;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = TcpStream::connect(addr).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    // every peer has its own thread (and therefore its own random numbers)
+    let mut peers = vec![(server, true), (client, false)]
+        .drain(..)
+        .map(|(stream, is_host)| {
+            std::thread::spawn(move || -> Result<(Session, usize, usize), String> {
+                let mut session = Session::new(stream).unwrap();
+                let generator = ObjectGenerator::new(Path::new("assets/data/"));
+
+                seed_rng(42);
+
+                let mut game = network_game(generator, TextureMap::default(), is_host);
+                if let Game::Combat(data) = &mut game {
+                    data.world.insert(Headless);
+                }
+
+                let (mut num_boosts, mut num_selections) = (0, 0);
+
+                loop {
+                    let data = match &game {
+                        Game::Combat(data)
+                            if data.turn.turn_number > 3
+                                || matches!(data.state, CombatState::GameOver(_)) =>
+                        {
+                            return Ok((session, num_boosts, num_selections));
+                        }
+                        Game::Combat(data) => data,
+                        _ => unreachable!(),
+                    };
+
+                    // play the local team like a user would (with the help of
+                    // the advisor)
+                    let cw = CoreWorld::new(&data.world);
+                    let teams = cw.teams();
+                    let local_team = data
+                        .waiting_for()
+                        .map(|t| teams.get(&t))
+                        .filter(|td| !td.team.is_remote);
+
+                    let input = match (&data.state, local_team) {
+                        (
+                            CombatState::WaitForUserInput(
+                                InputContext::ActivateActor {
+                                    team,
+                                    hand,
+                                    possible_actors,
+                                    ..
+                                },
+                                _,
+                            ),
+                            Some(td),
+                        ) => match possible_actors.values().min() {
+                            Some(id) if td.planned_boosts.is_empty() && !hand.is_empty() => {
+                                num_boosts += 1;
+                                Some(UserInput::BoostActivation(*id, *team, hand[0]))
+                            }
+                            _ => Some(UserInput::AssigneActivationDone(*team)),
+                        },
+
+                        (
+                            CombatState::WaitForUserInput(
+                                InputContext::SelectAction {
+                                    options,
+                                    suggestion,
+                                },
+                                _,
+                            ),
+                            Some(td),
+                        ) => {
+                            let mut positions = options
+                                .iter()
+                                .filter(|(_, actions)| !actions.is_empty())
+                                .map(|(p, _)| *p)
+                                .collect::<Vec<_>>();
+
+                            positions.sort_by_key(|MapPos(x, y)| (*y, *x));
+
+                            if td.advisor {
+                                num_selections += 1;
+                                suggestion
+                                    .or_else(|| positions.first().map(|p| (*p, 0)))
+                                    .map(|(p, idx)| UserInput::SelectPlayerAction(p, idx))
+                            } else {
+                                Some(UserInput::ToggleAdvisor(td.team.id))
+                            }
+                        }
+
+                        (_, Some(td)) => Some(UserInput::AutoResolveTurn(td.team.id)),
+                        (_, None) => None,
+                    };
+
+                    drop(teams);
+                    drop(cw);
+
+                    let input = session.exchange(&game, input)?;
+                    game = step(game, &input);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // (the sessions are kept until both peers are done)
+    let results = peers
+        .drain(..)
+        .map(|p| p.join().unwrap())
+        .collect::<Vec<_>>();
+
+    for r in results {
+        let (_, num_boosts, num_selections) = r.unwrap();
+
+        // both card assignments and action selections went over the wire
+        assert!(num_boosts > 0);
+        assert!(num_selections > 0);
+    }
+}
@@ -16,16 +16,17 @@ use ron::de::from_reader;
 use crate::ui::{Font, FontFace, ProtoSpriteConfig};
 use crate::core::{SpriteConfig, SpriteSource, TextureMap};
 
-pub struct AssetRepo<'a> {
-    texture_creator: &'a TextureCreator<WindowContext>,
+/// All images and fonts (as textures of the canvas which draws them)
+pub struct AssetRepo<'a, C = WindowContext> {
+    texture_creator: &'a TextureCreator<C>,
 
     pub texture: Option<Texture<'a>>,
     pub textures: HashMap<String, Texture<'a>>,
-    pub fonts: [Option<Font<'a>>; 3],
+    pub fonts: [Option<Font<'a, C>>; 3],
 }
 
-impl<'a> AssetRepo<'a> {
-    pub fn new(texture_creator: &'a TextureCreator<sdl2::video::WindowContext>) -> AssetRepo<'a> {
+impl<'a, C> AssetRepo<'a, C> {
+    pub fn new(texture_creator: &'a TextureCreator<C>) -> AssetRepo<'a, C> {
         Self {
             texture_creator,
             texture: None,
@@ -0,0 +1,112 @@
+use crate::core::{CombatData, CoreWorld, DisplayStr, Suite, TeamId, UserInput};
+use crate::ui::types::{ClickArea, ClickAreas, Scene, ScreenPos, ScreenText};
+
+use super::details::card_color;
+
+const ROW_WIDTH: u32 = 800;
+const ROW_HEIGHT: i32 = 60;
+
+/// Shows every card of the team's deck (grouped by suite); a click anywhere
+/// goes back to the arena
+pub fn render(
+    (x, y, w, h): (i32, i32, u32, u32),
+    game: &CombatData,
+    team: TeamId,
+) -> (Scene, ClickAreas) {
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+    let team_data = teams.get(&team);
+    let deck = &team_data.deck;
+    let (r, g, b) = team.colour();
+    let mut scene = Scene::empty().set_background(r, g, b);
+    let row_x = x + (w as i32 - ROW_WIDTH as i32) / 2;
+    let mut row_y = y + 20;
+
+    scene.texts.push(
+        ScreenText::new(
+            DisplayStr::new(format!(
+                "The deck of team {}: {} cards ({} left until the next shuffle)\n\
+                 Click somewhere to go back ...",
+                team_data.team.name,
+                deck.all_cards().len(),
+                deck.num_cards_left(),
+            )),
+            ScreenPos(row_x, row_y),
+        )
+        .width(ROW_WIDTH)
+        .padding(10)
+        .border(3, (23, 22, 21, 255))
+        .background((252, 251, 250, 255)),
+    );
+
+    row_y += 2 * ROW_HEIGHT;
+
+    let mut cards = deck.all_cards().to_vec();
+    cards.sort_by_key(|c| c.value);
+
+    // (cursed cards are worth nothing no matter their suite)
+    let groups = [
+        ("Clubs", Some(Suite::PhysicalStr)),
+        ("Spades", Some(Suite::PhysicalAg)),
+        ("Hearts", Some(Suite::MentalStr)),
+        ("Diamonds", Some(Suite::MentalAg)),
+        ("Wild", Some(Suite::Any)),
+        ("Cursed", None),
+    ];
+
+    for (name, suite) in groups.iter() {
+        let group = cards
+            .iter()
+            .filter(|c| match suite {
+                Some(s) => c.value > 0 && c.suite == *s,
+                None => c.value == 0,
+            })
+            .collect::<Vec<_>>();
+
+        if group.is_empty() {
+            continue;
+        }
+
+        let values = if group[0].value == 0 {
+            format!("{} cards", group.len())
+        } else {
+            group
+                .iter()
+                .map(|c| short_value(c.value))
+                .collect::<Vec<_>>()
+                .join("  ")
+        };
+
+        scene.texts.push(
+            ScreenText::new(
+                DisplayStr::new(format!("{}: {}", name, values)),
+                ScreenPos(row_x, row_y),
+            )
+            .width(ROW_WIDTH)
+            .padding(10)
+            .color(card_color(group[0]))
+            .border(3, card_color(group[0]))
+            .background((253, 252, 251, 255)),
+        );
+
+        row_y += ROW_HEIGHT;
+    }
+
+    (
+        scene,
+        vec![ClickArea {
+            clipping_area: (x, y, w, h),
+            action: Box::new(|_| UserInput::ToggleDeckViewer),
+        }],
+    )
+}
+
+fn short_value(value: u8) -> String {
+    match value {
+        1 => "A".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        _ => format!("{}", value),
+    }
+}
@@ -1,18 +1,24 @@
 use crate::core::{
-    Action, Activation, Actor, Card, CombatData, CombatState, DisplayStr, Health, InputContext,
-    MapPos, SelectedPos, Suite, TeamId, Trait, TraitSource, UserInput, ID,
+    Action, Activation, Actor, Card, CombatData, CombatState, CoreWorld, DisplayStr, Health,
+    InputContext, MapPos, SelectedPos, Suite, TeamId, Trait, TraitSource, UserInput, ID,
 };
-use crate::ui::types::{ClickArea, ClickAreas, Scene, ScreenPos, ScreenText};
+use crate::ui::types::{ClickArea, ClickAreas, Scene, ScreenPos, ScreenText, Tooltip};
 
-const DLG_WIDTH: u32 = 400;
+use super::tooltips::describe_card;
+
+/// The width of the dialogs at the right border of the screen (the other
+/// panels keep clear of them)
+pub(super) const DLG_WIDTH: u32 = 400;
 const BTN_HEIGHT: u32 = 65;
 const CARD_WIDTH: u32 = 120;
 const CARD_HEIGHT: u32 = 150;
+const AI_BTN_WIDTH: u32 = 300;
 
 pub fn render(
     scene: &mut Scene,
     click_areas: &mut ClickAreas,
     viewport: (u32, u32),
+    dlg_y: i32,
     game: &CombatData,
 ) {
     if let CombatState::WaitForUserInput(ctxt, selected_pos) = &game.state {
@@ -21,14 +27,32 @@ pub fn render(
             objects: actors,
         }) = selected_pos
         {
-            draw_area_details(scene, viewport.0, *pos, actors);
+            draw_area_details(scene, viewport.0, dlg_y, *pos, actors);
+        }
 
-            if let InputContext::SelectAction { options, .. } = ctxt {
+        if !super::is_local_decision(game) {
+            // the other player (of a networked game) has to decide
+            draw_waiting_hint(scene, game);
+            return;
+        }
+
+        if let Some(SelectedPos { pos, .. }) = selected_pos {
+            if let InputContext::SelectAction {
+                options,
+                suggestion,
+            } = ctxt
+            {
                 let actions = options.get(pos);
-                draw_action_buttons(scene, click_areas, game, viewport, actions);
+                let advised = suggestion.filter(|(p, _)| p == pos).map(|(_, idx)| idx);
+                draw_action_buttons(scene, click_areas, game, viewport, *pos, actions, advised);
             }
         }
 
+        if let Some(team) = super::controlling_team(game) {
+            let selected_actor = selected_actor_at(selected_pos);
+            draw_ai_buttons(scene, click_areas, game, team, selected_actor);
+        }
+
         if let InputContext::ActivateActor {
             hand,
             selected_card_idx,
@@ -47,10 +71,30 @@ pub fn render(
             );
 
             draw_ready_button(scene, click_areas, viewport, *team);
+            draw_undo_button(scene, click_areas, viewport, game, *team);
         }
     };
 }
 
+fn draw_waiting_hint(scene: &mut Scene, game: &CombatData) {
+    if let Some(team) = game.waiting_for() {
+        let cw = CoreWorld::new(&game.world);
+        let name = cw.teams().get(&team).team.name;
+        let (r, g, b) = team.colour();
+
+        scene.texts.push(
+            ScreenText::new(
+                DisplayStr::new(format!("Waiting for team {} ...", name)),
+                ScreenPos(0, 2 * BTN_HEIGHT as i32),
+            )
+            .padding(10)
+            .border(3, (r, g, b, 255))
+            .background((252, 251, 250, 255))
+            .width(AI_BTN_WIDTH),
+        );
+    }
+}
+
 fn selected_actor_at(selected_pos: &Option<SelectedPos>) -> Option<ID> {
     if let Some(SelectedPos { objects, .. }) = selected_pos {
         for a in objects.iter() {
@@ -63,6 +107,7 @@ fn selected_actor_at(selected_pos: &Option<SelectedPos>) -> Option<ID> {
 fn draw_area_details(
     scene: &mut Scene,
     viewport_width: u32,
+    dlg_y: i32,
     MapPos(x, y): MapPos,
     actors: &Vec<Actor>,
 ) {
@@ -81,7 +126,7 @@ fn draw_area_details(
     let x = (viewport_width - DLG_WIDTH) as i32;
 
     scene.texts.push(
-        ScreenText::new(DisplayStr::new(txt), ScreenPos(x, 0))
+        ScreenText::new(DisplayStr::new(txt), ScreenPos(x, dlg_y))
             .width(DLG_WIDTH)
             .padding(10)
             .background((252, 251, 250, 255))
@@ -94,13 +139,15 @@ fn draw_action_buttons(
     click_areas: &mut ClickAreas,
     game: &CombatData,
     (viewport_width, viewport_height): (u32, u32),
+    pos: MapPos,
     actions: Option<&Vec<Action>>,
+    advised: Option<usize>,
 ) {
-    let mut action_buttons = create_action_buttons(game, actions);
+    let mut action_buttons = create_action_buttons(game, actions, advised);
     let x = (viewport_width - DLG_WIDTH) as i32;
     let mut y = (viewport_height - action_buttons.len() as u32 * BTN_HEIGHT) as i32;
 
-    for (text, action) in action_buttons.drain(..) {
+    for (text, idx) in action_buttons.drain(..) {
         scene.texts.push(
             // scene.texts[FontFace::Normal as usize].push(
             ScreenText::new(text, ScreenPos(x, y))
@@ -112,7 +159,7 @@ fn draw_action_buttons(
 
         click_areas.push(ClickArea {
             clipping_area: (x, y, DLG_WIDTH, BTN_HEIGHT),
-            action: Box::new(move |_| UserInput::SelectPlayerAction(action.clone())),
+            action: Box::new(move |_| UserInput::SelectPlayerAction(pos, idx)),
         });
 
         y += BTN_HEIGHT as i32;
@@ -139,6 +186,11 @@ fn draw_cards(
 
         draw_card(scene, card, ScreenPos(x, y));
 
+        scene.tooltips.push(Tooltip {
+            area: (x, y, CARD_WIDTH, CARD_HEIGHT),
+            text: DisplayStr::new(describe_card(card)),
+        });
+
         if is_selected {
             if let Some(selected_actor_id) = selected_actor {
                 let card = *card;
@@ -183,16 +235,107 @@ fn draw_ready_button(
     });
 }
 
+/// Allows to take back the last card assignment (if there is one)
+fn draw_undo_button(
+    scene: &mut Scene,
+    click_areas: &mut ClickAreas,
+    (viewport_width, viewport_height): (u32, u32),
+    game: &CombatData,
+    team: TeamId,
+) {
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+
+    if let Some(boost) = teams.get(&team).planned_boosts.last() {
+        let name = cw
+            .get_actor(boost.actor)
+            .map(|a| a.name.clone())
+            .unwrap_or_default();
+        let x = (viewport_width - DLG_WIDTH) as i32;
+        let y = (viewport_height - 2 * BTN_HEIGHT) as i32;
+
+        scene.texts.push(
+            ScreenText::new(
+                DisplayStr::new(format!("Undo (card for {})", name)),
+                ScreenPos(x, y),
+            )
+            .padding(10)
+            .border(3, (23, 22, 21, 255))
+            .background((252, 251, 250, 255))
+            .width(DLG_WIDTH),
+        );
+
+        click_areas.push(ClickArea {
+            clipping_area: (x, y, DLG_WIDTH, BTN_HEIGHT),
+            action: Box::new(move |_| UserInput::UndoBoost(team)),
+        });
+    }
+}
+
+fn draw_ai_buttons(
+    scene: &mut Scene,
+    click_areas: &mut ClickAreas,
+    game: &CombatData,
+    team: TeamId,
+    selected_actor: Option<ID>,
+) {
+    let cw = CoreWorld::new(&game.world);
+    let team_data = cw.teams().get(&team).clone();
+    let on_off = |b: bool| if b { "on" } else { "off" };
+
+    let mut buttons = vec![
+        (
+            "Auto-resolve turn".to_string(),
+            UserInput::AutoResolveTurn(team),
+        ),
+        (
+            format!("Advisor: {}", on_off(team_data.advisor)),
+            UserInput::ToggleAdvisor(team),
+        ),
+        (
+            format!("Deck ({} cards)", team_data.deck.all_cards().len()),
+            UserInput::ToggleDeckViewer,
+        ),
+        ("Options".to_string(), UserInput::ToggleOptions),
+    ];
+
+    if let Some(a) = selected_actor.and_then(|id| cw.get_actor(id)) {
+        if a.team == team && a.is_pc() {
+            let is_autopiloted = team_data.autopilot_actors.contains(&a.id);
+
+            buttons.push((
+                format!("AI controls {}: {}", a.name, on_off(is_autopiloted)),
+                UserInput::ToggleAutopilot(team, a.id),
+            ));
+        }
+    }
+
+    let mut y = 2 * BTN_HEIGHT as i32;
+
+    for (text, input) in buttons {
+        scene.texts.push(
+            ScreenText::new(DisplayStr::new(text), ScreenPos(0, y))
+                .padding(10)
+                .border(3, (23, 22, 21, 255))
+                .background((252, 251, 250, 255))
+                .width(AI_BTN_WIDTH),
+        );
+
+        click_areas.push(ClickArea {
+            clipping_area: (0, y, AI_BTN_WIDTH, BTN_HEIGHT),
+            action: Box::new(move |_| input.clone()),
+        });
+
+        y += BTN_HEIGHT as i32;
+    }
+}
+
 //////////////////////////////////////////////////
 // PRIVATE HELPER
 //
 
 fn draw_card(scene: &mut Scene, card: &Card, pos: ScreenPos) {
-    let color = match card.suite {
-        Suite::PhysicalStr | Suite::PhysicalAg => (23, 22, 21, 255),
-        Suite::MentalStr | Suite::MentalAg => (253, 22, 21, 255),
-        _ => panic!("Suite not allowed: {:?}", card.suite),
-    };
+    let color = card_color(card);
 
     scene.texts.push(
         ScreenText::new(DisplayStr::new(format_card(card)), pos)
@@ -205,11 +348,24 @@ fn draw_card(scene: &mut Scene, card: &Card, pos: ScreenPos) {
     );
 }
 
+/// Black and red for the usual suites (and something special for everything
+/// else)
+pub(super) fn card_color(card: &Card) -> (u8, u8, u8, u8) {
+    match card.suite {
+        _ if card.value == 0 => (110, 40, 140, 255),
+        Suite::PhysicalStr | Suite::PhysicalAg => (23, 22, 21, 255),
+        Suite::MentalStr | Suite::MentalAg => (253, 22, 21, 255),
+        Suite::Any => (190, 140, 20, 255),
+        _ => panic!("Suite not allowed: {:?}", card.suite),
+    }
+}
+
 fn button_text_for_player_actions(action: &Action, is_first: bool) -> DisplayStr {
     let str = match action {
         Action::DoNothing(..) => format!("Do nothing"),
         Action::MoveTo { .. } => format!("Move Here"),
         Action::Attack { attack, .. } => format!("{}", attack.name),
+        Action::Aid { aid, .. } => format!("{}", aid.name),
         Action::AddTrait { msg, .. } => msg.clone(),
         Action::ActivateActor(..) => format!("Activate"),
         _ => format!("Unnamed action: {:?}", action),
@@ -294,38 +450,27 @@ fn format_activation(a: &Activation) -> String {
 }
 
 fn format_card(card: &Card) -> String {
-    let value = match card.value {
-        1 => "A".to_string(),
-        11 => "J".to_string(),
-        12 => "Q".to_string(),
-        13 => "K".to_string(),
-        _ => format!("{}", card.value),
-    };
-
-    let suite = match card.suite {
-        Suite::PhysicalStr => "Clubs",
-        Suite::PhysicalAg => "Spades",
-        Suite::MentalStr => "Hearts",
-        Suite::MentalAg => "Diamonds",
-        Suite::Physical => "Black",
-        Suite::Mental => "Red",
-        Suite::Any => "Any",
-        _ => panic!("Non valid suite: {:?}", card.suite),
-    };
-
-    format!("{} of {}", value, suite)
+    card.to_string()
 }
 
 fn create_action_buttons(
     _game: &CombatData,
     actions: Option<&Vec<Action>>,
-) -> Vec<(DisplayStr, Action)> {
+    advised: Option<usize>,
+) -> Vec<(DisplayStr, usize)> {
     let mut result = vec![];
     let mut is_first = true;
 
     if let Some(actions) = actions {
-        for a in actions.iter() {
-            result.push((button_text_for_player_actions(&a, is_first), a.clone()));
+        for (idx, a) in actions.iter().enumerate() {
+            let text = button_text_for_player_actions(&a, is_first);
+            let text = if advised == Some(idx) {
+                DisplayStr::new(format!("{} (advised)", text))
+            } else {
+                text
+            };
+
+            result.push((text, idx));
             is_first = false;
         }
     }
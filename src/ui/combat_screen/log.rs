@@ -0,0 +1,86 @@
+use crate::core::{CombatData, DisplayStr, UserInput};
+use crate::ui::types::{ClickArea, ClickAreas, Scene, ScreenPos, ScreenText};
+
+use super::details::DLG_WIDTH;
+
+/// The log panel sits between the AI buttons (left) and the dialogs (right)
+const PANEL_X: i32 = 300;
+const PANEL_Y: i32 = 65;
+const BTN_WIDTH: u32 = 100;
+const BTN_HEIGHT: u32 = 50;
+/// The number of log events which are visible at once
+const NUM_EVENTS: usize = 5;
+
+pub fn render(
+    scene: &mut Scene,
+    click_areas: &mut ClickAreas,
+    (viewport_width, _): (u32, u32),
+    log_offset: usize,
+    game: &CombatData,
+) {
+    let log = game.log();
+    let end = log.len().saturating_sub(log_offset);
+    let start = end.saturating_sub(NUM_EVENTS);
+    let width = viewport_width.saturating_sub(PANEL_X as u32 + DLG_WIDTH + BTN_WIDTH);
+
+    let txt = if log.is_empty() {
+        "Nothing has happened yet.".to_string()
+    } else {
+        log[start..end]
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    scene.texts.push(
+        ScreenText::new(DisplayStr::new(txt), ScreenPos(PANEL_X, PANEL_Y))
+            .padding(10)
+            .border(3, (23, 22, 21, 255))
+            .background((252, 251, 250, 200))
+            .width(width),
+    );
+
+    let btn_x = PANEL_X + width as i32;
+    let buttons = [
+        ("Older", UserInput::ScrollLog(NUM_EVENTS as i32), start > 0),
+        (
+            "Newer",
+            UserInput::ScrollLog(-(NUM_EVENTS as i32)),
+            log_offset > 0,
+        ),
+    ];
+
+    for (idx, (text, input, is_enabled)) in buttons.iter().enumerate() {
+        let y = PANEL_Y + idx as i32 * BTN_HEIGHT as i32;
+        let color = if *is_enabled {
+            (23, 22, 21, 255)
+        } else {
+            (153, 152, 151, 255)
+        };
+
+        scene.texts.push(
+            ScreenText::new(DisplayStr::new(*text), ScreenPos(btn_x, y))
+                .padding(10)
+                .color(color)
+                .border(3, (23, 22, 21, 255))
+                .background((252, 251, 250, 255))
+                .width(BTN_WIDTH)
+                .height(BTN_HEIGHT),
+        );
+
+        if *is_enabled {
+            let input = input.clone();
+
+            click_areas.push(ClickArea {
+                clipping_area: (btn_x, y, BTN_WIDTH, BTN_HEIGHT),
+                action: Box::new(move |_| input.clone()),
+            });
+        }
+    }
+}
+
+/// How far the log panel can be scrolled back
+pub fn max_log_offset(game: &CombatData) -> usize {
+    game.log().len().saturating_sub(NUM_EVENTS)
+}
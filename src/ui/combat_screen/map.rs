@@ -3,12 +3,14 @@ use std::vec;
 
 use specs::prelude::*;
 
-use crate::components::{Position, Sprites, Text, ZLayerFX, ZLayerFloor, ZLayerGameObject};
+use crate::components::{
+    ActorCmp, Position, Sprites, Text, ZLayerFX, ZLayerFloor, ZLayerGameObject,
+};
 use crate::core::{
-    CombatData, CombatState, InputContext, Map, MapPos, TextureMap, Tile, TileType, UserInput,
-    WorldPos,
+    find_charge_path, team_vision, Action, CombatData, CombatState, CoreWorld, InputContext, Map,
+    MapPos, TextureMap, Tile, TileType, UserInput, Vision, WorldPos,
 };
-use crate::ui::{Align, ClickArea, Scene, ScreenCoord, ScreenPos, ScreenSprite};
+use crate::ui::{Align, Camera, ClickArea, Scene, ScreenCoord, ScreenPos, ScreenSprite};
 
 pub type SystemData<'a> = (
     ReadStorage<'a, Position>,
@@ -17,66 +19,102 @@ pub type SystemData<'a> = (
     ReadStorage<'a, ZLayerFloor>,
     ReadStorage<'a, ZLayerGameObject>,
     ReadStorage<'a, ZLayerFX>,
+    ReadStorage<'a, ActorCmp>,
     Read<'a, Map>,
     Read<'a, TextureMap>,
 );
 
 type DefaultAction = (Option<MapPos>, HashMap<MapPos, UserInput>);
 
+/// The horizontal space between the attack icons of the same target
+const ICON_SPACING: i32 = 32;
+
 pub fn render(
     viewport: (i32, i32, u32, u32),
-    scroll_offset: (i32, i32),
+    camera: Camera,
+    hovered: Option<MapPos>,
     game: &CombatData,
 ) -> (Scene, Vec<ClickArea>) {
-    let (pos, sprites, texts, zlayer_floor, zlayer_gameobj, zlayer_fx, map, texture_map): SystemData = game.world.system_data();
+    let (pos, sprites, texts, zlayer_floor, zlayer_gameobj, zlayer_fx, actors, map, texture_map): SystemData = game.world.system_data();
     let mut scene = Scene::empty().set_background(252, 246, 218);
     let default_action = get_default_action(&game);
+    let vision = player_vision(game);
 
     // (NOTE: sprites added first to the scene appear "behind" sprites which are added later)
     // (1) draw map tiles
     render_map(
         &mut scene,
-        scroll_offset,
+        camera,
         default_action.0,
+        get_suggestion(game),
         &map,
         &texture_map,
+        &vision,
     );
 
     // (2) draw items on the ground (e.g. blood drops, ...)
-    render_floor_objects(&mut scene, scroll_offset, &pos, &sprites, &zlayer_floor);
+    render_floor_objects(&mut scene, camera, &pos, &sprites, &zlayer_floor);
 
     // (3) draw game objects (e.g. characters, obstacles, ...)
-    render_game_objects(&mut scene, scroll_offset, &pos, &sprites, &zlayer_gameobj);
-
-    // (4) draw visual effects
-    render_fx(
+    render_game_objects(
         &mut scene,
-        scroll_offset,
-        &default_action,
-        &texture_map,
+        camera,
         &pos,
         &sprites,
-        &zlayer_fx,
+        &zlayer_gameobj,
+        &actors,
+        &vision,
+    );
+
+    // (4) draw visual effects (and the overlays)
+    render_fx(&mut scene, camera, &pos, &sprites, &zlayer_fx, &vision);
+    render_icons(
+        &mut scene,
+        camera,
+        get_icons(game, hovered)
+            .into_iter()
+            .chain(get_reinforcement_icons(game))
+            .collect(),
+        &texture_map,
     );
 
     // (5) draw texts which are positioned relative to game objects
-    render_texts(&mut scene, scroll_offset, &pos, &texts);
+    render_texts(&mut scene, camera, &pos, &texts, &actors, &vision);
+
+    // (6) zoom everything (the positions are already zoomed)
+    zoom(&mut scene, camera.zoom);
 
     (
         scene,
-        render_action_buttons(viewport, scroll_offset, default_action),
+        render_action_buttons(viewport, camera, default_action),
     )
 }
 
+fn zoom(scene: &mut Scene, zoom: f32) {
+    for ScreenSprite(_, _, sprite) in scene.sprites.iter_mut() {
+        let (dx, dy) = sprite.offset;
+
+        sprite.scale *= zoom;
+        sprite.offset = (
+            (dx as f32 * zoom).round() as i32,
+            (dy as f32 * zoom).round() as i32,
+        );
+    }
+
+    for text in scene.texts.iter_mut() {
+        text.scale *= zoom;
+    }
+}
+
 fn render_floor_objects<'a>(
     scene: &mut Scene,
-    offset: (i32, i32),
+    camera: Camera,
     positions: &ReadStorage<Position>,
     visuals: &ReadStorage<Sprites>,
     zlayer_floor: &ReadStorage<ZLayerFloor>,
 ) {
     for (_, p, sprite_cmp) in (zlayer_floor, positions, visuals).join() {
-        for sprite in sprite_cmp.sample(ScreenCoord::from_world_pos(p.0).to_screen_pos(offset)) {
+        for sprite in sprite_cmp.sample(ScreenCoord::from_world_pos(p.0).to_screen_pos(camera)) {
             scene.sprites.push(sprite);
         }
     }
@@ -84,39 +122,56 @@ fn render_floor_objects<'a>(
 
 fn render_fx<'a>(
     scene: &mut Scene,
-    offset: (i32, i32),
-    default_action: &DefaultAction,
-    texture_map: &TextureMap,
+    camera: Camera,
     positions: &ReadStorage<Position>,
     visuals: &ReadStorage<Sprites>,
     zlayer_fx: &ReadStorage<ZLayerFX>,
+    vision: &Option<Vision>,
 ) {
     for (_, p, sprite_cmp) in (zlayer_fx, positions, visuals).join() {
-        for sprite in sprite_cmp.sample(ScreenCoord::from_world_pos(p.0).to_screen_pos(offset)) {
+        if !is_pos_visible(p.0, vision) {
+            continue;
+        }
+
+        for sprite in sprite_cmp.sample(ScreenCoord::from_world_pos(p.0).to_screen_pos(camera)) {
             scene.sprites.push(sprite);
         }
     }
+}
+
+fn render_icons(
+    scene: &mut Scene,
+    camera: Camera,
+    icons: Vec<(WorldPos, String, i32)>,
+    texture_map: &TextureMap,
+) {
+    for (wp, icon_name, dx) in icons {
+        if let Some(icon_sprite) = texture_map.get(&icon_name) {
+            let p = ScreenCoord::from_world_pos(wp).to_screen_pos(camera);
+            let mut sprite = icon_sprite.sample(0);
 
-    for (wp, icon_name) in get_icons(default_action) {
-        let icon_sprite = texture_map.get(&icon_name).unwrap();
-        let p = ScreenCoord::from_world_pos(wp).to_screen_pos(offset);
+            sprite.offset.0 += dx;
 
-        scene
-            .sprites
-            .push(ScreenSprite(p, Align::MidCenter, icon_sprite.sample(0)));
+            scene
+                .sprites
+                .push(ScreenSprite(p, Align::MidCenter, sprite));
+        }
     }
 }
 
 fn render_game_objects<'a>(
     scene: &mut Scene,
-    offset: (i32, i32),
+    camera: Camera,
     positions: &ReadStorage<Position>,
     visuals: &ReadStorage<Sprites>,
     zlayer_gameobj: &ReadStorage<ZLayerGameObject>,
+    actors: &ReadStorage<ActorCmp>,
+    vision: &Option<Vision>,
 ) {
-    let mut data = (zlayer_gameobj, positions, visuals)
+    let mut data = (zlayer_gameobj, positions, visuals, actors.maybe())
         .join()
-        .map(|(_, Position(p), sc)| (ScreenCoord::from_world_pos(*p), sc))
+        .filter(|(_, _, _, a)| is_visible(*a, vision))
+        .map(|(_, Position(p), sc, _)| (ScreenCoord::from_world_pos(*p), sc))
         .collect::<Vec<_>>();
 
     data.sort_by(|(p1, _), (p2, _)| {
@@ -128,7 +183,7 @@ fn render_game_objects<'a>(
     });
 
     for (p, sprite_cmp) in data {
-        for sprite in sprite_cmp.sample(p.to_screen_pos(offset)) {
+        for sprite in sprite_cmp.sample(p.to_screen_pos(camera)) {
             scene.sprites.push(sprite);
         }
     }
@@ -136,15 +191,28 @@ fn render_game_objects<'a>(
 
 fn render_texts<'a>(
     scene: &mut Scene,
-    offset: (i32, i32),
+    camera: Camera,
     positions: &ReadStorage<Position>,
     texts: &ReadStorage<Text>,
+    actors: &ReadStorage<ActorCmp>,
+    vision: &Option<Vision>,
 ) {
-    for (pos, text) in (positions, texts).join() {
-        let ScreenPos(mut x, mut y) = ScreenCoord::from_world_pos(pos.0).to_screen_pos(offset);
+    for (pos, text, a) in (positions, texts, actors.maybe()).join() {
+        // (texts like damage numbers are not attached to an actor but must not
+        // give away where the hidden ones are either)
+        let is_hidden = match a {
+            Some(_) => !is_visible(a, vision),
+            None => !is_pos_visible(pos.0, vision),
+        };
+
+        if is_hidden {
+            continue;
+        }
+
+        let ScreenPos(mut x, mut y) = ScreenCoord::from_world_pos(pos.0).to_screen_pos(camera);
         if let Some((dx, dy)) = text.offset {
-            x += dx;
-            y += dy;
+            x += (dx as f32 * camera.zoom).round() as i32;
+            y += (dy as f32 * camera.zoom).round() as i32;
         }
 
         scene.texts.push(text.into_screen_text(ScreenPos(x, y)));
@@ -153,36 +221,60 @@ fn render_texts<'a>(
 
 fn render_map(
     scene: &mut Scene,
-    offset: (i32, i32),
+    camera: Camera,
     selected_pos: Option<MapPos>,
+    suggestion: Option<MapPos>,
     map: &Map,
     texture_map: &TextureMap,
+    vision: &Option<Vision>,
 ) {
     for tile in map.tiles() {
         if let Some(sprite_config) = map_tile_to_texture(tile).and_then(|tn| texture_map.get(&tn)) {
             let tile_pos = tile.to_world_pos();
-            let p = ScreenCoord::from_world_pos(tile_pos).to_screen_pos(offset);
+            let p = ScreenCoord::from_world_pos(tile_pos).to_screen_pos(camera);
+            let mut sprite = sprite_config.sample(0);
+
+            if let Some(v) = vision {
+                if !v.is_visible(tile.to_map_pos()) {
+                    // fog of war
+                    sprite.alpha /= 3;
+                }
+            }
 
             scene
                 .sprites
-                .push(ScreenSprite(p, Align::MidCenter, sprite_config.sample(0)));
+                .push(ScreenSprite(p, Align::MidCenter, sprite));
         }
     }
 
     if let Some(p) = selected_pos {
         if let Some(sprite_config) = texture_map.get("selected") {
-            let p = ScreenCoord::from_world_pos(p.to_world_pos()).to_screen_pos(offset);
+            let p = ScreenCoord::from_world_pos(p.to_world_pos()).to_screen_pos(camera);
 
             scene
                 .sprites
                 .push(ScreenSprite(p, Align::MidCenter, sprite_config.sample(0)));
         }
     }
+
+    if let Some(p) = suggestion {
+        if let Some(sprite_config) = texture_map.get("selected") {
+            let p = ScreenCoord::from_world_pos(p.to_world_pos()).to_screen_pos(camera);
+            let mut sprite = sprite_config.sample(0);
+
+            // a fainter marker than the one for the selected position
+            sprite.alpha /= 2;
+
+            scene
+                .sprites
+                .push(ScreenSprite(p, Align::MidCenter, sprite));
+        }
+    }
 }
 
 fn render_action_buttons<'a>(
     viewport: (i32, i32, u32, u32),
-    scroll_offset: (i32, i32),
+    camera: Camera,
     default_action: DefaultAction,
 ) -> Vec<ClickArea> {
     let mut click_areas = vec![];
@@ -190,7 +282,7 @@ fn render_action_buttons<'a>(
     click_areas.push(ClickArea {
         clipping_area: viewport,
         action: Box::new(move |screen_pos| {
-            let clicked_pos = screen_pos_to_map_pos(screen_pos, scroll_offset);
+            let clicked_pos = screen_pos_to_map_pos(screen_pos, camera);
 
             if let Some(selected_pos) = default_action.0 {
                 if clicked_pos == selected_pos {
@@ -207,24 +299,47 @@ fn render_action_buttons<'a>(
     click_areas
 }
 
-fn screen_pos_to_map_pos(screen_pos: ScreenPos, scroll_offset: (i32, i32)) -> MapPos {
-    MapPos::from_world_pos(
-        ScreenCoord::new(
-            screen_pos.0 - scroll_offset.0,
-            screen_pos.1 - scroll_offset.1,
-        )
-        .to_world_pos(),
-    )
+pub(super) fn screen_pos_to_map_pos(screen_pos: ScreenPos, camera: Camera) -> MapPos {
+    MapPos::from_world_pos(camera.to_screen_coord(screen_pos).to_world_pos())
+}
+
+/// Returns what the player controlled team in charge can see (or None if all
+/// teams are controlled by the AI)
+pub(super) fn player_vision(game: &CombatData) -> Option<Vision> {
+    let cw = CoreWorld::new(&game.world);
+
+    super::controlling_team(game).map(|t| team_vision(t, &cw))
+}
+
+/// Game objects which are not actors (e.g. walls) are always visible
+fn is_visible(a: Option<&ActorCmp>, vision: &Option<Vision>) -> bool {
+    match (a, vision) {
+        (Some(ActorCmp(a)), Some(v)) => v.can_see_actor(a),
+        _ => true,
+    }
+}
+
+/// Whether the tile at the given position is not hidden by the fog of war
+fn is_pos_visible(p: WorldPos, vision: &Option<Vision>) -> bool {
+    vision
+        .as_ref()
+        .map(|v| v.is_visible(MapPos::from_world_pos(p)))
+        .unwrap_or(true)
 }
 
 fn map_tile_to_texture(t: Tile) -> Option<String> {
     match t.tile_type() {
         TileType::Floor => Some(String::from("floor")),
-        _ => None,
+        TileType::Rough => Some(String::from("floor-rough")),
+        TileType::Water => Some(String::from("floor-water")),
+        TileType::Pit => Some(String::from("floor-pit")),
+        TileType::HighGround => Some(String::from("floor-high")),
+        TileType::Hazard => Some(String::from("floor-hazard")),
+        TileType::Void => None,
     }
 }
 
-fn get_default_action(game: &CombatData) -> DefaultAction {
+pub(super) fn get_default_action(game: &CombatData) -> DefaultAction {
     if let CombatState::WaitForUserInput(ctxt, selected_pos) = &game.state {
         let selected_mpos = selected_pos.as_ref().map(|sp| sp.pos.clone());
 
@@ -245,14 +360,11 @@ fn get_default_action(game: &CombatData) -> DefaultAction {
                 return (selected_mpos, activations);
             }
 
-            InputContext::SelectAction { options } => {
+            InputContext::SelectAction { options, .. } => {
                 let mut user_inputs = HashMap::new();
                 for (pos, player_actions) in options.iter() {
                     if !player_actions.is_empty() {
-                        user_inputs.insert(
-                            *pos,
-                            UserInput::SelectPlayerAction(player_actions.first().unwrap().clone()),
-                        );
+                        user_inputs.insert(*pos, UserInput::SelectPlayerAction(*pos, 0));
                     }
                 }
                 return (selected_mpos, user_inputs);
@@ -267,9 +379,151 @@ fn get_default_action(game: &CombatData) -> DefaultAction {
     (None, HashMap::new())
 }
 
-fn get_icons(action: &DefaultAction) -> Vec<(WorldPos, String)> {
-    match &action.1 {
-        // Some(player_action) => map_player_action_to_floor_icon(player_action),
+/// The position of the action the advisor suggests (if any)
+fn get_suggestion(game: &CombatData) -> Option<MapPos> {
+    if let CombatState::WaitForUserInput(InputContext::SelectAction { suggestion, .. }, _) =
+        &game.state
+    {
+        return suggestion.map(|(p, _)| p);
+    }
+
+    None
+}
+
+/// Warns about the tiles where reinforcements will arrive next turn
+fn get_reinforcement_icons(game: &CombatData) -> Vec<(WorldPos, String, i32)> {
+    game.turn
+        .upcoming_reinforcements()
+        .into_iter()
+        .filter(|(eta, ..)| *eta == 1)
+        .map(|(_, _, pos)| {
+            (
+                pos.to_world_pos(),
+                "icon-floor-Reinforcements".to_string(),
+                0,
+            )
+        })
+        .collect()
+}
+
+/// The overlays which show the player what the active actor can do: where it
+/// can move to, whom it can attack (and how) and which path it would take to
+/// get to the hovered position
+fn get_icons(game: &CombatData, hovered: Option<MapPos>) -> Vec<(WorldPos, String, i32)> {
+    let options = match &game.state {
+        CombatState::WaitForUserInput(InputContext::SelectAction { options, .. }, _)
+            if super::is_local_decision(game) =>
+        {
+            options
+        }
+        _ => return vec![],
+    };
+
+    let cw = CoreWorld::new(&game.world);
+    let mut icons = vec![];
+
+    for (pos, actions) in options.iter() {
+        let wp = pos.to_world_pos();
+        let mut attack_icons: Vec<&str> = vec![];
+
+        for action in actions {
+            match action {
+                Action::MoveTo { .. } => icons.push((wp, "icon-floor-MoveTo".to_string(), 0)),
+
+                Action::Attack {
+                    attacker,
+                    attack,
+                    attack_vector,
+                    ..
+                } => {
+                    let from = cw
+                        .get_actor(*attacker)
+                        .map(|a| MapPos::from_world_pos(a.pos));
+                    let icon = match from.map(|p| p.distance(*pos)) {
+                        Some(d) if d > attack.max_distance as usize => "icon-action-Charge",
+                        _ if attack.max_distance > 1 => "icon-action-RangedAttack",
+                        _ => "icon-action-MeleeAttack",
+                    };
+
+                    if !attack_icons.contains(&icon) {
+                        attack_icons.push(icon);
+                    }
+
+                    if icon == "icon-action-RangedAttack" {
+                        let is_obscured = attack_vector.iter().any(|(_, _, c, _)| c.obscured > 0);
+                        let floor_icon = if is_obscured {
+                            "icon-floor-RangedAttack-2"
+                        } else {
+                            "icon-floor-RangedAttack-1"
+                        };
+
+                        icons.push((wp, floor_icon.to_string(), 0));
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        for (idx, icon) in attack_icons.iter().enumerate() {
+            icons.push((wp, icon.to_string(), -(idx as i32) * ICON_SPACING));
+        }
+    }
+
+    if let Some(action) = hovered
+        .and_then(|p| options.get(&p))
+        .and_then(|a| a.first())
+    {
+        for (p, dot) in planned_path(action, &cw) {
+            icons.push((p, dot.to_string(), 0));
+        }
+    }
+
+    icons
+}
+
+/// The tiles the actor passes when performing the given action (e.g. the
+/// tiles it moves over or the tiles it charges across)
+fn planned_path(action: &Action, cw: &CoreWorld) -> Vec<(WorldPos, &'static str)> {
+    match action {
+        Action::MoveTo { actor, path } => {
+            let start = cw.get_actor(*actor).map(|a| MapPos::from_world_pos(a.pos));
+
+            path.iter()
+                .filter(|t| Some(t.to_map_pos()) != start)
+                .map(|t| (t.to_world_pos(), "icon-dot-blue"))
+                .collect()
+        }
+
+        Action::Attack {
+            attacker,
+            target,
+            attack,
+            ..
+        } => {
+            let (attacker, target) = match (cw.get_actor(*attacker), cw.get_actor(*target)) {
+                (Some(a), Some(t)) => (a, t),
+                _ => return vec![],
+            };
+
+            let d =
+                MapPos::from_world_pos(attacker.pos).distance(MapPos::from_world_pos(target.pos));
+            let advance_distance = d.saturating_sub(attack.max_distance.into());
+            if advance_distance == 0 {
+                return vec![];
+            }
+
+            find_charge_path(attacker, target.pos, cw)
+                .map(|path| {
+                    path.iter()
+                        .skip(1)
+                        .take(advance_distance)
+                        .map(|t| (t.to_world_pos(), "icon-dot-yellow"))
+                        .collect()
+                })
+                .unwrap_or_default()
+        }
+
         _ => vec![],
     }
 }
@@ -0,0 +1,212 @@
+use specs::prelude::*;
+
+use crate::core::{CombatData, CoreWorld, DisplayStr, Map, TileType, UserInput, WorldPos};
+use crate::ui::types::{
+    Camera, ClickArea, ClickAreas, Scene, ScreenCoord, ScreenPos, ScreenText, Tooltip, TILE_HEIGHT,
+    TILE_WIDTH,
+};
+
+/// The width (and height) of the minimap panel
+pub const MINIMAP_SIZE: u32 = 200;
+const PADDING: u32 = 10;
+/// The distance of the off-screen indicators to the edge of the window
+const ARROW_MARGIN: i32 = 10;
+const ARROW_SIZE: u32 = 40;
+
+/// Shows the whole arena (scaled down) in the top right corner; a click on it
+/// moves the camera there
+pub fn render(
+    scene: &mut Scene,
+    click_areas: &mut ClickAreas,
+    (viewport_width, viewport_height): (u32, u32),
+    camera: Camera,
+    game: &CombatData,
+) {
+    let map: Read<Map> = game.world.system_data();
+    let cw = CoreWorld::new(&game.world);
+    let vision = super::map::player_vision(game);
+    let panel_x = (viewport_width - MINIMAP_SIZE) as i32;
+    let (num_columns, num_rows) = (map.num_columns(), map.num_rows());
+    let map_size = TILE_WIDTH * (num_columns + num_rows) / 2;
+    let scale = (MINIMAP_SIZE - 2 * PADDING) as f32 / map_size as f32;
+
+    // the left-most (and upper-most) point of the arena in unscaled screen
+    // coordinates (see ScreenCoord)
+    let (min_x, min_y) = (
+        -((TILE_WIDTH / 2 * num_rows) as i32),
+        -(TILE_HEIGHT as i32 / 2),
+    );
+
+    let to_minimap = |(x, y): (i32, i32)| {
+        ScreenPos(
+            panel_x + PADDING as i32 + ((x - min_x) as f32 * scale).round() as i32,
+            PADDING as i32 + ((y - min_y) as f32 * scale).round() as i32,
+        )
+    };
+
+    let tile_w = ((TILE_WIDTH as f32 * scale).round() as u32).max(1);
+    let tile_h = ((TILE_HEIGHT as f32 * scale / 2.0).round() as u32).max(1);
+
+    scene.rects.push((
+        (panel_x, 0, MINIMAP_SIZE, MINIMAP_SIZE),
+        (252, 251, 250, 220),
+    ));
+
+    for tile in map.tiles() {
+        if let Some(color) = tile_color(tile.tile_type()) {
+            let ScreenPos(x, y) = to_minimap(unscaled(tile.to_world_pos()));
+
+            scene.rects.push((
+                (x - tile_w as i32 / 2, y - tile_h as i32 / 2, tile_w, tile_h),
+                color,
+            ));
+        }
+    }
+
+    for a in cw.actors() {
+        if !a.is_alive() || !vision.as_ref().map(|v| v.can_see_actor(a)).unwrap_or(true) {
+            continue;
+        }
+
+        let (r, g, b) = a.team.colour();
+        let ScreenPos(x, y) = to_minimap(unscaled(a.pos));
+
+        scene.rects.push((
+            (x - tile_h as i32, y - tile_h as i32, 2 * tile_h, 2 * tile_h),
+            (r, g, b, 255),
+        ));
+    }
+
+    // the part of the arena which is visible in the main view
+    let (ox, oy) = camera.offset;
+    let to_unscaled = |p: i32, o: i32| ((p - o) as f32 / camera.zoom).round() as i32;
+    let ScreenPos(x0, y0) = to_minimap((to_unscaled(0, ox), to_unscaled(0, oy)));
+    let ScreenPos(x1, y1) = to_minimap((
+        to_unscaled(viewport_width as i32, ox),
+        to_unscaled(viewport_height as i32, oy),
+    ));
+    let (x0, x1) = (x0.max(panel_x), x1.min(panel_x + MINIMAP_SIZE as i32));
+    let (y0, y1) = (y0.max(0), y1.min(MINIMAP_SIZE as i32));
+
+    if x0 < x1 && y0 < y1 {
+        let (w, h) = ((x1 - x0) as u32, (y1 - y0) as u32);
+        let color = (23, 22, 21, 255);
+
+        scene.rects.push(((x0, y0, w, 1), color));
+        scene.rects.push(((x0, y1 - 1, w, 1), color));
+        scene.rects.push(((x0, y0, 1, h), color));
+        scene.rects.push(((x1 - 1, y0, 1, h), color));
+    }
+
+    click_areas.push(ClickArea {
+        clipping_area: (panel_x, 0, MINIMAP_SIZE, MINIMAP_SIZE),
+        action: Box::new(move |ScreenPos(px, py)| {
+            // center the main view on the clicked position
+            let x = ((px - panel_x - PADDING as i32) as f32 / scale).round() as i32 + min_x;
+            let y = ((py - PADDING as i32) as f32 / scale).round() as i32 + min_y;
+            let sx = (x as f32 * camera.zoom).round() as i32 + ox;
+            let sy = (y as f32 * camera.zoom).round() as i32 + oy;
+
+            UserInput::ScrollBy(
+                viewport_width as i32 / 2 - sx,
+                viewport_height as i32 / 2 - sy,
+            )
+        }),
+    });
+}
+
+/// Shows arrows at the edge of the main view which point at the enemies (and
+/// the upcoming reinforcements) outside of it
+pub fn render_off_screen_indicators(
+    scene: &mut Scene,
+    (viewport_width, viewport_height): (u32, u32),
+    camera: Camera,
+    game: &CombatData,
+) {
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+    let vision = super::map::player_vision(game);
+    let team = match super::controlling_team(game) {
+        Some(team) => team,
+        None => return,
+    };
+
+    let enemies = cw
+        .actors()
+        .filter(|a| a.is_alive() && teams.is_hostile(team, a.team))
+        .filter(|a| vision.as_ref().map(|v| v.can_see_actor(a)).unwrap_or(true))
+        .map(|a| (a.pos, a.team, a.name.clone()));
+
+    let reinforcements = game
+        .turn
+        .upcoming_reinforcements()
+        .into_iter()
+        .map(|(eta, t, pos)| {
+            let txt = format!("Reinforcements (in {} turns)", eta);
+            (pos.to_world_pos(), t, txt)
+        });
+
+    for (wp, t, txt) in enemies
+        .collect::<Vec<_>>()
+        .into_iter()
+        .chain(reinforcements)
+    {
+        let ScreenPos(x, y) = ScreenCoord::from_world_pos(wp).to_screen_pos(camera);
+        let (w, h) = (viewport_width as i32, viewport_height as i32);
+
+        if x >= 0 && x < w && y >= 0 && y < h {
+            continue;
+        }
+
+        let (cx, cy) = (w / 2, h / 2);
+        let arrow = if (x - cx).abs() * h > (y - cy).abs() * w {
+            if x < cx {
+                "<"
+            } else {
+                ">"
+            }
+        } else if y < cy {
+            "^"
+        } else {
+            "v"
+        };
+
+        let size = ARROW_SIZE as i32;
+        let ax = x.max(ARROW_MARGIN).min(w - ARROW_MARGIN - size);
+        let ay = y.max(ARROW_MARGIN).min(h - ARROW_MARGIN - size);
+        let (r, g, b) = t.colour();
+
+        scene.texts.push(
+            ScreenText::new(DisplayStr::new(arrow), ScreenPos(ax, ay))
+                .width(ARROW_SIZE)
+                .height(ARROW_SIZE)
+                .padding(5)
+                .color((r, g, b, 255))
+                .border(3, (r, g, b, 255))
+                .background((252, 251, 250, 255)),
+        );
+
+        scene.tooltips.push(Tooltip {
+            area: (ax, ay, ARROW_SIZE, ARROW_SIZE),
+            text: DisplayStr::new(txt),
+        });
+    }
+}
+
+/// The position in screen pixels without any zoom or scrolling
+fn unscaled(wp: WorldPos) -> (i32, i32) {
+    let ScreenPos(x, y) = ScreenCoord::from_world_pos(wp).to_screen_pos(Camera::new((0, 0)));
+    (x, y)
+}
+
+fn tile_color(tile_type: TileType) -> Option<(u8, u8, u8, u8)> {
+    match tile_type {
+        TileType::Floor => Some((200, 190, 160, 255)),
+        TileType::Rough => Some((150, 120, 80, 255)),
+        TileType::Water => Some((80, 130, 200, 255)),
+        TileType::Pit => Some((40, 35, 30, 255)),
+        TileType::HighGround => Some((120, 160, 90, 255)),
+        TileType::Hazard => Some((200, 60, 40, 255)),
+        TileType::Void => None,
+    }
+}
@@ -1,29 +1,174 @@
+mod deck;
 mod details;
+mod log;
 mod map;
+mod minimap;
+mod timeline;
+mod tooltips;
+
+pub use log::max_log_offset;
 
 use specs::prelude::*;
 
 use super::types::*;
 
-use crate::core::{CombatData, CombatPhase, DisplayStr, Map, TurnState};
+use crate::core::{
+    CombatData, CombatPhase, CombatState, CoreWorld, DisplayStr, InputContext, Map, TeamId,
+    TurnState, UserInput, WorldPos, ID,
+};
+
+pub fn render(ui: &UI, game: &CombatData) -> (Scene, ClickAreas) {
+    let (x, y, w, h) = ui.viewport;
+    let camera = ui
+        .scrolling
+        .as_ref()
+        .map(|s| s.camera)
+        .unwrap_or_else(|| Camera::new((0, 0)));
+    let hovered = ui.mouse_pos.map(|p| map::screen_pos_to_map_pos(p, camera));
+
+    if let CombatState::WaitForUserInput(InputContext::HandOver { team }, _) = &game.state {
+        return render_hand_over((x, y, w, h), game, *team);
+    }
+
+    if ui.show_deck {
+        if let Some(team) = controlling_team(game) {
+            return deck::render((x, y, w, h), game, team);
+        }
+    }
 
-pub fn render(
-    (x, y, w, h): (i32, i32, u32, u32),
-    scroll_offset: (i32, i32),
-    game: &CombatData,
-) -> (Scene, ClickAreas) {
     let mut click_areas: ClickAreas = vec![];
-    let (mut scene, mut map_clicks) = map::render((x, y, w, h), scroll_offset, game);
+    let (mut scene, mut map_clicks) = map::render((x, y, w, h), camera, hovered, game);
+
+    // (the dialogs on the right start below the minimap)
+    let dlg_y = if ui.show_minimap {
+        minimap::render(&mut scene, &mut click_areas, (w, h), camera, game);
+        minimap::MINIMAP_SIZE as i32
+    } else {
+        0
+    };
+
+    minimap::render_off_screen_indicators(&mut scene, (w, h), camera, game);
+    details::render(&mut scene, &mut click_areas, (w, h), dlg_y, game);
+    log::render(&mut scene, &mut click_areas, (w, h), ui.log_offset, game);
+    timeline::render(&mut scene, (w, h), game);
+
+    if let (Some(p), Some(pos)) = (ui.mouse_pos, hovered) {
+        // (buttons, cards, ... hide the map)
+        let is_on_map = !click_areas.iter().any(|c| p.is_inside(c.clipping_area));
 
-    details::render(&mut scene, &mut click_areas, (w, h), game);
+        if let Some(text) = tooltips::describe_map_pos(pos, game).filter(|_| is_on_map) {
+            scene.tooltips.push(Tooltip {
+                area: (x, y, w, h),
+                text: DisplayStr::new(text),
+            });
+        }
+    }
 
     render_screen_texts(&mut scene, game);
+    render_game_over(&mut scene, (x, y, w, h), game);
 
     click_areas.append(&mut map_clicks);
 
     (scene, click_areas)
 }
 
+/// What clicking the selected position (again) would do
+pub fn default_input(game: &CombatData) -> Option<UserInput> {
+    let (selected, inputs) = map::get_default_action(game);
+    inputs.get(&selected?).cloned()
+}
+
+/// Hides the map (and all hands) until the next player has taken over
+fn render_hand_over(
+    (x, y, w, h): (i32, i32, u32, u32),
+    game: &CombatData,
+    team: TeamId,
+) -> (Scene, ClickAreas) {
+    let cw = CoreWorld::new(&game.world);
+    let name = cw.teams().get(&team).team.name;
+    let (r, g, b) = team.colour();
+    let mut scene = Scene::empty().set_background(r, g, b);
+
+    scene.texts.push(
+        ScreenText::new(
+            DisplayStr::new(format!(
+                "Team {}, it's your turn!\n\nClick somewhere to continue ...",
+                name
+            )),
+            ScreenPos(x + w as i32 / 2 - 200, y + h as i32 / 2 - 50),
+        )
+        .width(400)
+        .padding(20)
+        .border(3, (23, 22, 21, 255))
+        .background((252, 251, 250, 255)),
+    );
+
+    (
+        scene,
+        vec![ClickArea {
+            clipping_area: (x, y, w, h),
+            action: Box::new(move |_| UserInput::HandOverDone(team)),
+        }],
+    )
+}
+
+/// Announces the end of the battle (from the view of the local player)
+fn render_game_over(scene: &mut Scene, (x, y, w, h): (i32, i32, u32, u32), game: &CombatData) {
+    let winners = match &game.state {
+        CombatState::GameOver(winners) => winners,
+        _ => return,
+    };
+
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+    let names = winners
+        .iter()
+        .map(|t| teams.get(t).team.name)
+        .collect::<Vec<_>>();
+
+    let txt = match controlling_team(game) {
+        Some(t) if !teams.is_hot_seat() && winners.contains(&t) => "Victory!".to_string(),
+        Some(_) if !teams.is_hot_seat() => "Defeat!".to_string(),
+        _ if names.is_empty() => "No one is left standing".to_string(),
+        _ => format!("Victory for {}!", names.join(", ")),
+    };
+
+    scene.texts.push(
+        ScreenText::new(
+            DisplayStr::new(format!("{}\n\nPress Escape to leave the arena", txt)),
+            ScreenPos(x + w as i32 / 2 - 200, y + h as i32 / 2 - 50),
+        )
+        .width(400)
+        .padding(20)
+        .border(3, (23, 22, 21, 255))
+        .background((252, 251, 250, 255)),
+    );
+}
+
+/// The local player controlled team which currently has to make a decision
+/// (or the first one if it is someone else's turn); in a hot-seat game this
+/// is also the team whose view of the arena is displayed
+fn controlling_team(game: &CombatData) -> Option<TeamId> {
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+    let is_local_pc = |t: &TeamId| {
+        let team = &teams.get(t).team;
+        team.is_pc && !team.is_remote
+    };
+
+    game.waiting_for()
+        .or_else(|| cw.find_actor(|a| a.active).map(|a| a.team))
+        .or_else(|| game.turn.get_active_team())
+        .filter(is_local_pc)
+        .or_else(|| teams.iter().map(|td| td.team.id).find(is_local_pc))
+}
+
+/// Checks if the game waits for a decision of the local player
+fn is_local_decision(game: &CombatData) -> bool {
+    let team = game.waiting_for();
+    team.is_some() && team == controlling_team(game)
+}
+
 fn render_screen_texts(
     scene: &mut Scene,
     // viewport: (i32, i32, u32, u32),
@@ -45,6 +190,21 @@ fn render_screen_texts(
     );
 }
 
+/// The active actor and its position if the camera should follow it (because
+/// the local player does not control it)
+pub fn camera_focus(game: &CombatData) -> Option<(ID, WorldPos)> {
+    let cw = CoreWorld::new(&game.world);
+    let teams = cw.teams();
+    let actor = cw.find_actor(|a| a.active)?;
+    let team = teams.get(&actor.team);
+
+    if team.is_autopiloted(actor.id) || team.team.is_remote {
+        Some((actor.id, actor.pos))
+    } else {
+        None
+    }
+}
+
 pub fn init_scroll_offset(
     game: &CombatData,
     (viewport_width, viewport_height): (u32, u32),
@@ -0,0 +1,83 @@
+use crate::core::{Activation, CombatData, CoreWorld, DisplayStr};
+use crate::ui::types::{Scene, ScreenPos, ScreenText, Tooltip};
+
+use super::details::DLG_WIDTH;
+
+/// The entries line up from left to right above the hand of cards (as many
+/// as fit in until the dialogs)
+const TIMELINE_X: i32 = 300;
+/// The distance of the entries' top edge to the bottom of the screen
+const TIMELINE_BOTTOM: i32 = 255;
+const ENTRY_WIDTH: u32 = 110;
+const ENTRY_HEIGHT: u32 = 65;
+const ENTRY_SPACING: u32 = 5;
+
+/// Shows who acts next: the active actor (highlighted) followed by the
+/// remaining activations of every visible actor (in the order of their
+/// initiative)
+pub fn render(scene: &mut Scene, (viewport_width, viewport_height): (u32, u32), game: &CombatData) {
+    let cw = CoreWorld::new(&game.world);
+    let vision = super::map::player_vision(game);
+    let max_entries = viewport_width.saturating_sub(TIMELINE_X as u32 + DLG_WIDTH)
+        / (ENTRY_WIDTH + ENTRY_SPACING);
+
+    let active = cw
+        .find_actor(|a| a.active)
+        .and_then(|a| a.active_activation.clone().map(|act| (a.id, act, true)));
+
+    let entries = active
+        .into_iter()
+        .chain(
+            game.initiative_order()
+                .into_iter()
+                .map(|(id, act)| (id, act, false)),
+        )
+        .filter_map(|(id, act, is_active)| {
+            cw.get_actor(id)
+                .filter(|a| vision.as_ref().map(|v| v.can_see_actor(a)).unwrap_or(true))
+                .map(|a| (a.clone(), act, is_active))
+        })
+        .take(max_entries as usize);
+
+    let y = viewport_height as i32 - TIMELINE_BOTTOM;
+
+    for (idx, (a, act, is_active)) in entries.enumerate() {
+        let x = TIMELINE_X + idx as i32 * (ENTRY_WIDTH + ENTRY_SPACING) as i32;
+        let (r, g, b) = a.team.colour();
+        let (border, background) = if is_active {
+            (6, (255, 240, 190, 255))
+        } else {
+            (3, (252, 251, 250, 255))
+        };
+
+        scene.texts.push(
+            ScreenText::new(
+                DisplayStr::new(format!("{}\n{}", act.initiativ(), a.name)),
+                ScreenPos(x, y),
+            )
+            .width(ENTRY_WIDTH)
+            .height(ENTRY_HEIGHT)
+            .padding(5)
+            .border(border, (r, g, b, 255))
+            .background(background),
+        );
+
+        scene.tooltips.push(Tooltip {
+            area: (x, y, ENTRY_WIDTH, ENTRY_HEIGHT),
+            text: DisplayStr::new(describe_entry(&a.name, &act, is_active)),
+        });
+    }
+}
+
+fn describe_entry(name: &str, act: &Activation, is_active: bool) -> String {
+    let cards = match act {
+        Activation::Single(c) => c.to_string(),
+        Activation::Boosted(c1, c2) => format!("the best of {} and {}", c1, c2),
+    };
+
+    if is_active {
+        format!("{} acts now with {}", name, cards)
+    } else {
+        format!("{} acts with {}", name, cards)
+    }
+}
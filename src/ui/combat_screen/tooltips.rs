@@ -0,0 +1,237 @@
+use crate::core::{
+    Actor, AidEffect, AttributeModifier, Card, CombatData, CoreWorld, DefenceType, Effect,
+    HitEffect, MapPos, Suite, TileType, Trait, TraitSource,
+};
+
+/// The suites of the usual cards (wild cards match any suite)
+const SUITES: [Suite; 4] = [
+    Suite::PhysicalStr,
+    Suite::PhysicalAg,
+    Suite::MentalStr,
+    Suite::MentalAg,
+];
+
+/// Describes the tile at the given position and the actor on it (if the
+/// player can see it)
+pub(super) fn describe_map_pos(pos: MapPos, game: &CombatData) -> Option<String> {
+    let cw = CoreWorld::new(&game.world);
+    let tile = cw.map().get_tile(pos)?;
+    let mut txt = describe_tile(tile.tile_type())?.to_string();
+    let vision = super::map::player_vision(game);
+
+    let actor = cw.find_actor(|a| {
+        MapPos::from_world_pos(a.pos) == pos
+            && vision.as_ref().map(|v| v.can_see_actor(a)).unwrap_or(true)
+    });
+
+    if let Some(a) = actor {
+        txt += &format!("\n\n{}", describe_actor(&a));
+    }
+
+    let is_spawn_point = game
+        .turn
+        .upcoming_reinforcements()
+        .iter()
+        .any(|(eta, _, p)| *eta == 1 && *p == pos);
+
+    if is_spawn_point {
+        txt += "\n\nReinforcements arrive here next turn";
+    }
+
+    Some(txt)
+}
+
+/// Explains which actions a card is good for
+pub(super) fn describe_card(card: &Card) -> String {
+    if card.value == 0 {
+        return format!("{}\nWorthless for any action", card);
+    }
+
+    let matching = |factor: u8| {
+        SUITES
+            .iter()
+            .filter(|s| Card { value: 2, ..*card }.value(**s) == factor)
+            .map(|s| suite_name(*s))
+            .collect::<Vec<_>>()
+    };
+
+    let full = matching(2);
+    let half = matching(1);
+    let mut txt = format!("{}\nFull value for: {}", card, full.join(", "));
+
+    if !half.is_empty() {
+        txt += &format!("\nHalf value for: {}", half.join(", "));
+    }
+
+    txt
+}
+
+fn describe_tile(tile_type: TileType) -> Option<&'static str> {
+    match tile_type {
+        TileType::Floor => Some("Floor"),
+        TileType::Rough => Some("Rough ground\nSlows down anyone who walks"),
+        TileType::Water => {
+            Some("Shallow water\nSlows down anyone who walks; cannot be crossed underground")
+        }
+        TileType::Pit => Some("Pit\nDeadly for anyone who cannot fly"),
+        TileType::HighGround => Some("High ground\nBetter attacks from up here"),
+        TileType::Hazard => Some("Hazard\nHurts anyone who steps on it"),
+        TileType::Void => None,
+    }
+}
+
+fn describe_actor(a: &Actor) -> String {
+    let mut txt = format!(
+        "{}\nWounds: {}/{} (pain: {})\nMove: {}\nBody: strength {}, agility {}\nMind: strength {}, agility {}",
+        a.name,
+        a.health.recieved_wounds,
+        a.health.max_wounds,
+        a.health.pain,
+        a.move_distance(),
+        a.skill(Suite::PhysicalStr, 0),
+        a.skill(Suite::PhysicalAg, 0),
+        a.skill(Suite::MentalStr, 0),
+        a.skill(Suite::MentalAg, 0),
+    );
+
+    for t in a.active_traits() {
+        txt += &format!("\n- {}", describe_trait(t));
+    }
+
+    txt
+}
+
+fn describe_trait(t: &Trait) -> String {
+    let source = match t.source {
+        TraitSource::IntrinsicProperty => "".to_string(),
+        TraitSource::Temporary(rounds_left) => format!(" ({} left)", rounds_left),
+    };
+
+    let effects = t
+        .effects
+        .iter()
+        .filter_map(describe_effect)
+        .collect::<Vec<_>>();
+
+    if effects.is_empty() {
+        format!("{}{}", t.name, source)
+    } else {
+        format!("{}{}: {}", t.name, source, effects.join("; "))
+    }
+}
+
+fn describe_effect(e: &Effect) -> Option<String> {
+    match e {
+        Effect::AttrMod(attr, val) => Some(format!("{:?} {:+}", attr, val)),
+
+        Effect::Mod(m, val) => Some(format!("{} {:+}", modifier_name(*m), val)),
+
+        Effect::AttackSingleTarget {
+            name,
+            to_hit,
+            to_wound,
+            distance_min,
+            distance_max,
+            rend,
+            effects,
+            ..
+        } => {
+            let mut txt = format!(
+                "{} hits with {} {:+}, wounds with {} {:+}, range {}-{}",
+                name,
+                suite_name(to_hit.0),
+                to_hit.1,
+                suite_name(to_wound.0),
+                to_wound.1,
+                distance_min.unwrap_or(0),
+                distance_max.unwrap_or(1),
+            );
+
+            if let Some(rend) = rend.filter(|r| *r > 0) {
+                txt += &format!(", rend {}", rend);
+            }
+
+            for (_, hit_effect) in effects.iter().flatten() {
+                txt += &match hit_effect {
+                    HitEffect::PushBack(d) => format!(", on hit: pushes back {}", d),
+                    HitEffect::PullCloser(d) => format!(", on hit: pulls closer {}", d),
+                };
+            }
+
+            Some(txt)
+        }
+
+        Effect::Aid {
+            distance_max,
+            effects,
+            ..
+        } => {
+            let effects = effects
+                .iter()
+                .map(|e| match e {
+                    AidEffect::Heal(w) => format!("heals {} wounds", w),
+                    AidEffect::Stabilize(p) => format!("relieves {} pain", p),
+                    AidEffect::Revive => "revives".to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            Some(format!(
+                "{} (range {})",
+                effects.join(", "),
+                distance_max.unwrap_or(1)
+            ))
+        }
+
+        Effect::Defence(val, defence) => {
+            let name = match defence {
+                DefenceType::Dodge(..) => "dodge",
+                DefenceType::Block => "block",
+                DefenceType::Parry => "parry",
+                DefenceType::TakeCover => "take cover",
+            };
+
+            Some(format!("{} {:+}", name, val))
+        }
+
+        Effect::Keyword(kw) => Some(format!("{:?}", kw)),
+
+        _ => None,
+    }
+}
+
+fn suite_name(s: Suite) -> &'static str {
+    match s {
+        Suite::PhysicalStr => "physical strength",
+        Suite::PhysicalAg => "physical agility",
+        Suite::MentalStr => "mental strength",
+        Suite::MentalAg => "mental agility",
+        Suite::Physical => "physical",
+        Suite::Mental => "mental",
+        Suite::Strength => "strength",
+        Suite::Agility => "agility",
+        Suite::Any => "anything",
+    }
+}
+
+fn modifier_name(m: AttributeModifier) -> &'static str {
+    match m {
+        AttributeModifier::PhysicalStrength => "physical strength",
+        AttributeModifier::PhysicalAgility => "physical agility",
+        AttributeModifier::PhysicalResistence => "physical resistence",
+        AttributeModifier::MentalStrength => "mental strength",
+        AttributeModifier::MentalAgility => "mental agility",
+        AttributeModifier::MentalResitence => "mental resistence",
+        AttributeModifier::MoveDistance => "move distance",
+    }
+}
+
+#[test]
+fn test_cards_explain_their_suite() {
+    let txt = describe_card(&Card::new(7, Suite::PhysicalStr));
+
+    assert_eq!(
+        txt,
+        "7 of Clubs\nFull value for: physical strength\nHalf value for: physical agility, mental strength"
+    );
+    assert!(describe_card(&Card::new(0, Suite::Any)).contains("Worthless"));
+}
@@ -1,21 +1,38 @@
 use sdl2::rect::{Rect, Point};
-use sdl2::event::Event as Sdl2Event;
-use sdl2::keyboard::Keycode;
+use sdl2::event::{Event as Sdl2Event, WindowEvent};
 use sdl2::EventPump;
 
 use crate::core::*;
-use crate::ui::{ClickArea, ClickAreas, ScreenPos, UI};
+use crate::ui::{key_input, ClickArea, KeyAction, ClickAreas, ScreenPos, UI};
+
+pub fn poll(
+    sdl_events: &mut EventPump,
+    click_areas: &ClickAreas,
+    ui: &UI,
+    game: &Game,
+) -> Option<UserInput> {
+    let mouse = sdl_events.mouse_state();
+    let mut hover = None;
 
-pub fn poll(sdl_events: &mut EventPump, click_areas: &ClickAreas, ui: &UI) -> Option<UserInput> {
     for event in sdl_events.poll_iter() {
         match event {
-            Sdl2Event::Quit { .. }
-            | Sdl2Event::KeyDown {
-                keycode: Some(Keycode::Escape),
-                ..
-            } => return Some(UserInput::Exit()),
+            Sdl2Event::Quit { .. } => return Some(UserInput::Exit()),
 
-            Sdl2Event::MouseMotion { xrel, yrel, .. } => {
+            Sdl2Event::KeyDown {
+                keycode: Some(key), ..
+            } => {
+                // (the options screen is controlled by mouse only)
+                let action = ui
+                    .keys
+                    .get(key)
+                    .filter(|a| !ui.show_options || *a == KeyAction::Exit);
+
+                if let Some(input) = action.and_then(|a| key_input(a, game)) {
+                    return Some(input);
+                }
+            }
+
+            Sdl2Event::MouseMotion { x, y, xrel, yrel, .. } => {
                 let is_scrolling = ui.scrolling.as_ref().map(|s| s.is_scrolling).unwrap_or(false);
                 if is_scrolling {
                     return Some(UserInput::ScrollTo(
@@ -23,6 +40,49 @@ pub fn poll(sdl_events: &mut EventPump, click_areas: &ClickAreas, ui: &UI) -> Op
                         ui.pixel_ratio as i32 * yrel,
                     ));
                 }
+
+                let p = ScreenPos(ui.pixel_ratio as i32 * x, ui.pixel_ratio as i32 * y);
+
+                if let Some(sd) = ui.scrolling.as_ref() {
+                    let (dx, dy) = edge_direction(ui.viewport, p);
+
+                    if sd.edge_scrolling != (dx, dy) {
+                        return Some(UserInput::EdgeScroll(dx, dy));
+                    }
+                }
+
+                // (only the latest position matters)
+                hover = Some(p);
+            }
+
+            Sdl2Event::Window {
+                win_event: WindowEvent::SizeChanged(w, h),
+                ..
+            } => {
+                return Some(UserInput::WindowResized(
+                    ui.pixel_ratio as u32 * w as u32,
+                    ui.pixel_ratio as u32 * h as u32,
+                ));
+            }
+
+            Sdl2Event::Window {
+                win_event: WindowEvent::Leave,
+                ..
+            }
+            | Sdl2Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                return Some(UserInput::EdgeScroll(0, 0));
+            }
+
+            Sdl2Event::MouseWheel { y, .. } if y != 0 => {
+                let p = (
+                    ui.pixel_ratio as i32 * mouse.x(),
+                    ui.pixel_ratio as i32 * mouse.y(),
+                );
+
+                return Some(UserInput::Zoom(y, p));
             }
 
             Sdl2Event::MouseButtonUp { x, y, .. } => {
@@ -48,9 +108,30 @@ pub fn poll(sdl_events: &mut EventPump, click_areas: &ClickAreas, ui: &UI) -> Op
         }
     }
 
-    None
+    hover
+        .filter(|p| ui.mouse_pos != Some(*p))
+        .map(|ScreenPos(x, y)| UserInput::Hover(x, y))
 }
 
+/// The mouse scrolls the arena if it is this close to the edge of the window
+const EDGE_SIZE: i32 = 10;
+
+fn edge_direction((x, y, w, h): (i32, i32, u32, u32), p: ScreenPos) -> (i32, i32) {
+    let direction = |pos: i32, min: i32, max: i32| {
+        if pos < min + EDGE_SIZE {
+            -1
+        } else if pos >= max - EDGE_SIZE {
+            1
+        } else {
+            0
+        }
+    };
+
+    (
+        direction(p.0, x, x + w as i32),
+        direction(p.1, y, y + h as i32),
+    )
+}
 
 fn contains_point((x, y, w, h): (i32, i32, u32, u32), p: ScreenPos) -> bool {
     Rect::new(x, y, w, h).contains_point(Point::new(p.0, p.1))
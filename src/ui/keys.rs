@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
+use ron::de::from_reader;
+use sdl2::keyboard::Keycode;
+use serde::Deserialize;
+
+use crate::core::{CombatData, CombatState, CoreWorld, Game, InputContext, MapPos, UserInput};
+
+use super::combat_screen;
+
+/// The number of pixels the camera moves per key press
+const PAN_STEP: i32 = 64;
+
+/// What a key does (the same key does different things depending on the
+/// screen and the input context)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum KeyAction {
+    Exit,
+    /// Moves the cursor (the selected position) along the axes of the map
+    MoveCursor(i32, i32),
+    /// Moves the camera (horizontally, vertically) across the screen
+    Pan(i32, i32),
+    /// Selects the next actor which can be activated (or which can act)
+    NextActor,
+    /// Does the same as clicking the selected position again (e.g. assigns
+    /// the selected card or performs the first action)
+    Confirm,
+    /// Ends the planning phase (see UserInput::AssigneActivationDone)
+    Ready,
+    /// Takes back the last assigned card
+    Undo,
+    /// Selects a card of the hand (starting with 0)
+    SelectCard(usize),
+    Screenshot,
+    ToggleMinimap,
+}
+
+pub struct KeyBindings(HashMap<Keycode, KeyAction>);
+
+impl KeyBindings {
+    /// Reads a list of key names (see SDL_GetKeyName) and their actions
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let f = File::open(path).map_err(|e| e.to_string())?;
+        let entries: Vec<(String, KeyAction)> = from_reader(f).map_err(|e| e.to_string())?;
+        let mut bindings = HashMap::new();
+
+        for (name, action) in entries {
+            let key = Keycode::from_name(&name).ok_or(format!("Unknown key: {}", name))?;
+            bindings.insert(key, action);
+        }
+
+        Ok(Self(bindings))
+    }
+
+    pub fn get(&self, key: Keycode) -> Option<KeyAction> {
+        self.0.get(&key).copied()
+    }
+}
+
+/// Translates a key action into the user input a mouse click would have
+/// caused (so the game does not need to know about the keyboard at all)
+pub fn key_input(action: KeyAction, game: &Game) -> Option<UserInput> {
+    match (action, game) {
+        (KeyAction::Exit, _) => Some(UserInput::Exit()),
+        (KeyAction::Screenshot, _) => Some(UserInput::Screenshot),
+        (KeyAction::Pan(dx, dy), Game::Combat(..)) => {
+            Some(UserInput::ScrollBy(-dx * PAN_STEP, -dy * PAN_STEP))
+        }
+        (KeyAction::ToggleMinimap, Game::Combat(..)) => Some(UserInput::ToggleMinimap),
+        (KeyAction::Confirm, Game::Start(..)) => Some(UserInput::NewGame),
+        (KeyAction::Confirm, Game::TeamSelection(..)) => Some(UserInput::SelectTeam(vec![])),
+        (_, Game::Combat(data)) => combat_key_input(action, data),
+        _ => None,
+    }
+}
+
+fn combat_key_input(action: KeyAction, game: &CombatData) -> Option<UserInput> {
+    let ctxt = match &game.state {
+        CombatState::WaitForUserInput(ctxt, _) => ctxt,
+        _ => return None,
+    };
+
+    match (action, ctxt) {
+        (KeyAction::Confirm, InputContext::HandOver { team }) => {
+            Some(UserInput::HandOverDone(*team))
+        }
+
+        (_, InputContext::HandOver { .. }) => None,
+
+        (KeyAction::MoveCursor(dx, dy), _) => {
+            let cw = CoreWorld::new(&game.world);
+            let map = cw.map();
+            let MapPos(x, y) = cursor_pos(game);
+            let x = (x + dx).max(0).min(map.num_columns() as i32 - 1);
+            let y = (y + dy).max(0).min(map.num_rows() as i32 - 1);
+
+            Some(UserInput::SelectWorldPos(MapPos(x, y)))
+        }
+
+        (KeyAction::NextActor, _) => {
+            let mut candidates = match ctxt {
+                InputContext::ActivateActor {
+                    possible_actors, ..
+                } => possible_actors.keys().copied().collect::<Vec<_>>(),
+
+                InputContext::SelectAction { options, .. } => options
+                    .iter()
+                    .filter(|(_, actions)| !actions.is_empty())
+                    .map(|(p, _)| *p)
+                    .collect::<Vec<_>>(),
+
+                InputContext::HandOver { .. } => vec![],
+            };
+
+            // row by row (the candidates come from a hash map)
+            candidates.sort_by_key(|MapPos(x, y)| (*y, *x));
+
+            let next = selected_pos(game)
+                .and_then(|p| candidates.iter().position(|c| *c == p))
+                .map(|idx| (idx + 1) % candidates.len())
+                .unwrap_or(0);
+
+            candidates.get(next).map(|p| UserInput::SelectWorldPos(*p))
+        }
+
+        (KeyAction::SelectCard(idx), InputContext::ActivateActor { hand, .. }) => {
+            if idx < hand.len() {
+                Some(UserInput::SelectActivationCard(idx))
+            } else {
+                None
+            }
+        }
+
+        (KeyAction::Confirm, _) => combat_screen::default_input(game),
+
+        (KeyAction::Ready, InputContext::ActivateActor { team, .. }) => {
+            Some(UserInput::AssigneActivationDone(*team))
+        }
+
+        (KeyAction::Undo, InputContext::ActivateActor { team, .. }) => {
+            Some(UserInput::UndoBoost(*team))
+        }
+
+        _ => None,
+    }
+}
+
+fn selected_pos(game: &CombatData) -> Option<MapPos> {
+    match &game.state {
+        CombatState::WaitForUserInput(_, Some(selected)) => Some(selected.pos),
+        _ => None,
+    }
+}
+
+/// The cursor starts at the selected position or else at the active actor
+fn cursor_pos(game: &CombatData) -> MapPos {
+    selected_pos(game)
+        .or_else(|| {
+            let cw = CoreWorld::new(&game.world);
+            cw.find_actor(|a| a.active)
+                .map(|a| MapPos::from_world_pos(a.pos))
+        })
+        .unwrap_or(MapPos(0, 0))
+}
+
+#[test]
+fn test_cards_can_be_assigned_with_the_keyboard() {
+    use crate::core::{step, Headless, ObjectGenerator, TextureMap};
+
+    // a single player game (as started from the menu)
+    let generator = ObjectGenerator::new(Path::new("assets/data/"));
+    let game = Game::Start(generator, TextureMap::default());
+    let game = step(game, &Some(UserInput::NewGame));
+    let mut game = step(game, &Some(UserInput::SelectTeam(vec![])));
+    if let Game::Combat(data) = &mut game {
+        data.world.insert(Headless);
+    }
+
+    let press = |action, game| {
+        let input = key_input(action, &game);
+        assert!(input.is_some(), "{:?} does nothing", action);
+        step(game, &input)
+    };
+
+    for _ in 0..1000 {
+        match &game {
+            Game::Combat(CombatData {
+                state: CombatState::WaitForUserInput(InputContext::ActivateActor { .. }, _),
+                ..
+            }) => break,
+            _ => game = step(game, &None),
+        }
+    }
+
+    let game = press(KeyAction::SelectCard(0), game);
+    let game = press(KeyAction::NextActor, game);
+
+    let (id, team, card) = match &game {
+        Game::Combat(data) => match &data.state {
+            CombatState::WaitForUserInput(
+                InputContext::ActivateActor {
+                    team,
+                    hand,
+                    possible_actors,
+                    ..
+                },
+                Some(selected),
+            ) => (possible_actors[&selected.pos], *team, hand[0]),
+            s => panic!("Unexpected state: {:?}", s),
+        },
+        _ => unreachable!(),
+    };
+
+    match key_input(KeyAction::Confirm, &game) {
+        Some(UserInput::BoostActivation(i, t, c)) => {
+            assert_eq!((i, t, c), (id, team, card));
+        }
+        i => panic!("Unexpected input: {:?}", i),
+    }
+}
@@ -1,6 +1,10 @@
 mod asset;
 mod combat_screen;
 mod input;
+mod keys;
+mod options_screen;
+mod screenshot;
+mod settings;
 mod start_screen;
 mod teams_screen;
 mod text;
@@ -8,38 +12,48 @@ mod types;
 
 pub use asset::*;
 pub use input::*;
+pub use keys::*;
+pub use screenshot::*;
 use sdl2::render::Texture;
+pub use settings::*;
 pub use text::*;
 pub use types::*;
 
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
+use sdl2::render::{BlendMode, Canvas, RenderTarget};
 use std::time::Instant;
 
-use crate::core::{Direction, DisplayStr, Game, Sprite, UserInput};
+use crate::core::{CombatData, Direction, DisplayStr, Game, Sprite, UserInput};
 
-pub fn render(
-    cvs: &mut WindowCanvas,
+/// The number of pixels the camera moves per frame (see edge scrolling)
+const EDGE_SCROLL_SPEED: i32 = 10;
+/// Each step of the mouse wheel zooms in (or out) by this factor
+const ZOOM_STEP: f32 = 1.25;
+const TOOLTIP_WIDTH: u32 = 400;
+
+pub fn render<T: RenderTarget, C>(
+    cvs: &mut Canvas<T>,
     ui: &UI,
     game: &Game,
-    assets: &mut AssetRepo,
+    assets: &mut AssetRepo<C>,
 ) -> Result<ClickAreas, String> {
     // let now = Instant::now();
     let (mut scene, click_areas) = match game {
+        _ if ui.show_options => options_screen::render(ui.viewport, &ui.settings),
+
         Game::Start(..) => start_screen::render(ui.viewport),
 
-        Game::TeamSelection(_, _, actors) => {
+        Game::TeamSelection(_, _, actors, _) => {
             let (_, _, w, h) = ui.viewport;
             teams_screen::render((w, h), actors)
         }
 
-        Game::Combat(combat_data) => {
-            let scroll_offset = ui.scrolling.as_ref().map(|s| s.offset).unwrap_or((0, 0));
-            combat_screen::render(ui.viewport, scroll_offset, combat_data)
-        }
+        Game::Combat(combat_data) => combat_screen::render(ui, combat_data),
     };
 
+    add_tooltip(&mut scene, ui);
+
     scene.texts.push(
         ScreenText::new(
             DisplayStr::new(format!("FPS: {}", ui.fps)),
@@ -61,7 +75,11 @@ pub fn render(
     Ok(click_areas)
 }
 
-fn draw_scene(cvs: &mut WindowCanvas, assets: &mut AssetRepo, scene: Scene) -> Result<(), String> {
+fn draw_scene<T: RenderTarget, C>(
+    cvs: &mut Canvas<T>,
+    assets: &mut AssetRepo<C>,
+    scene: Scene,
+) -> Result<(), String> {
     let (r, g, b) = scene.background;
 
     cvs.set_draw_color(Color::RGB(r, g, b));
@@ -81,6 +99,13 @@ fn draw_scene(cvs: &mut WindowCanvas, assets: &mut AssetRepo, scene: Scene) -> R
         }
     }
 
+    cvs.set_blend_mode(BlendMode::Blend);
+
+    for ((x, y, w, h), (r, g, b, a)) in scene.rects {
+        cvs.set_draw_color(Color::RGBA(r, g, b, a));
+        cvs.fill_rect(Rect::new(x, y, w, h))?;
+    }
+
     for txt in scene.texts {
         let font = assets.fonts[txt.font as usize].as_mut().unwrap();
         font.draw(txt, cvs)?;
@@ -91,20 +116,66 @@ fn draw_scene(cvs: &mut WindowCanvas, assets: &mut AssetRepo, scene: Scene) -> R
     Ok(())
 }
 
-pub fn init_ui(viewport: (i32, i32, u32, u32), pixel_ratio: u8) -> UI {
+/// Shows the tooltip the mouse points at next to the mouse (towards the middle
+/// of the screen so it is not cut off)
+fn add_tooltip(scene: &mut Scene, ui: &UI) {
+    let p = match ui.mouse_pos {
+        Some(p) => p,
+        None => return,
+    };
+
+    if let Some(tooltip) = scene.tooltips.iter().find(|t| t.contains(p)) {
+        let (_, _, w, h) = ui.viewport;
+        let ScreenPos(x, y) = p;
+        let (align, dx, dy) = match (x > w as i32 / 2, y > h as i32 / 2) {
+            (false, false) => (Align::TopLeft, 20, 20),
+            (true, false) => (Align::TopRight, -10, 20),
+            (false, true) => (Align::BottomLeft, 20, -10),
+            (true, true) => (Align::BottomRight, -10, -10),
+        };
+
+        scene.texts.push(
+            ScreenText::new(tooltip.text.clone(), ScreenPos(x + dx, y + dy))
+                .align(align)
+                .width(TOOLTIP_WIDTH)
+                .padding(10)
+                .background((252, 251, 250, 255))
+                .border(3, (23, 22, 21, 255)),
+        );
+    }
+}
+
+pub fn init_ui(
+    viewport: (i32, i32, u32, u32),
+    pixel_ratio: u8,
+    keys: KeyBindings,
+    settings: VideoSettings,
+) -> UI {
     UI {
+        keys,
+        settings,
+        show_options: false,
+        mouse_pos: None,
         viewport,
         pixel_ratio,
         fps: 0,
         frames: 0,
         last_check: Instant::now(),
         scrolling: None,
+        log_offset: 0,
+        show_deck: false,
+        show_minimap: true,
     }
 }
 
 pub fn step_ui(mut ui: UI, g: &Game, i: &Option<UserInput>) -> UI {
     ui = update_fps(ui);
+    ui = update_video_settings(ui, i);
     ui = update_scrolling(ui, g, i);
+    ui = update_log_offset(ui, g, i);
+    ui = update_deck_viewer(ui, g, i);
+    ui = update_minimap(ui, i);
+    ui = update_mouse_pos(ui, i);
     ui
 }
 
@@ -126,21 +197,118 @@ fn update_fps(ui: UI) -> UI {
     }
 }
 
+fn update_video_settings(ui: UI, i: &Option<UserInput>) -> UI {
+    let settings = ui.settings.clone();
+
+    match i {
+        Some(UserInput::ToggleOptions) => UI {
+            show_options: !ui.show_options,
+            ..ui
+        },
+
+        Some(UserInput::SetResolution(w, h)) => UI {
+            settings: VideoSettings {
+                resolution: (*w, *h),
+                ..settings
+            },
+            ..ui
+        },
+
+        Some(UserInput::ToggleFullscreen) => UI {
+            settings: VideoSettings {
+                fullscreen: !settings.fullscreen,
+                ..settings
+            },
+            ..ui
+        },
+
+        Some(UserInput::ToggleVsync) => UI {
+            settings: VideoSettings {
+                vsync: !settings.vsync,
+                ..settings
+            },
+            ..ui
+        },
+
+        Some(UserInput::WindowResized(w, h)) => UI {
+            viewport: (0, 0, *w, *h),
+            ..ui
+        },
+
+        _ => ui,
+    }
+}
+
+fn update_log_offset(ui: UI, g: &Game, i: &Option<UserInput>) -> UI {
+    let log_offset = match (g, i) {
+        (Game::Combat(combat_data), Some(UserInput::ScrollLog(delta))) => {
+            let max_offset = combat_screen::max_log_offset(combat_data) as i32;
+            (ui.log_offset as i32 + delta).max(0).min(max_offset) as usize
+        }
+
+        (Game::Combat(..), _) => ui.log_offset,
+
+        _ => 0,
+    };
+
+    UI { log_offset, ..ui }
+}
+
+fn update_deck_viewer(ui: UI, g: &Game, i: &Option<UserInput>) -> UI {
+    let show_deck = match (g, i) {
+        (Game::Combat(..), Some(UserInput::ToggleDeckViewer)) => !ui.show_deck,
+        (Game::Combat(..), _) => ui.show_deck,
+        _ => false,
+    };
+
+    UI { show_deck, ..ui }
+}
+
+fn update_minimap(ui: UI, i: &Option<UserInput>) -> UI {
+    match i {
+        Some(UserInput::ToggleMinimap) => UI {
+            show_minimap: !ui.show_minimap,
+            ..ui
+        },
+
+        _ => ui,
+    }
+}
+
+fn update_mouse_pos(ui: UI, i: &Option<UserInput>) -> UI {
+    match i {
+        Some(UserInput::Hover(x, y)) => UI {
+            mouse_pos: Some(ScreenPos(*x, *y)),
+            ..ui
+        },
+
+        _ => ui,
+    }
+}
+
 fn update_scrolling(ui: UI, g: &Game, i: &Option<UserInput>) -> UI {
     let scrolling = ui.scrolling;
     let (_, _, w, h) = ui.viewport;
 
     UI {
         scrolling: match (scrolling, g, i) {
-            (Some(sd), Game::Combat(..), None) => Some(sd),
-
             (None, Game::Combat(combat_data), _) => Some(ScrollData {
                 is_scrolling: false,
                 has_scrolled: false,
-                offset: combat_screen::init_scroll_offset(combat_data, (w, h)),
+                camera: Camera::new(combat_screen::init_scroll_offset(combat_data, (w, h))),
+                edge_scrolling: (0, 0),
+                target: None,
+                followed: None,
             }),
 
-            (Some(sd), Game::Combat(..), Some(i)) => Some(get_scrolling(sd, i)),
+            (Some(sd), Game::Combat(combat_data), i) => {
+                let sd = match i {
+                    Some(i) => get_scrolling(sd, i),
+                    None => sd,
+                };
+
+                Some(move_camera(follow_actor(sd, combat_data, (w, h))))
+            }
 
             _ => None,
         },
@@ -171,11 +339,41 @@ fn update_scrolling(ui: UI, g: &Game, i: &Option<UserInput>) -> UI {
     // }
 }
 fn get_scrolling(sd: ScrollData, i: &UserInput) -> ScrollData {
+    match i {
+        UserInput::ScrollBy(dx, dy) => {
+            return ScrollData {
+                camera: sd.camera.scroll(*dx, *dy),
+                target: None,
+                ..sd
+            };
+        }
+
+        UserInput::EdgeScroll(dx, dy) => {
+            return ScrollData {
+                edge_scrolling: (*dx, *dy),
+                ..sd
+            };
+        }
+
+        UserInput::Zoom(steps, (x, y)) => {
+            let zoom = sd.camera.zoom * ZOOM_STEP.powi(*steps);
+
+            return ScrollData {
+                camera: sd.camera.zoom_at(zoom, ScreenPos(*x, *y)),
+                target: None,
+                ..sd
+            };
+        }
+
+        _ => {}
+    }
+
     if sd.is_scrolling {
         return match i {
             UserInput::ScrollTo(dx, dy) => ScrollData {
-                offset: (sd.offset.0 + dx, sd.offset.1 + dy),
+                camera: sd.camera.scroll(*dx, *dy),
                 has_scrolled: true,
+                target: None,
                 ..sd
             },
 
@@ -197,15 +395,86 @@ fn get_scrolling(sd: ScrollData, i: &UserInput) -> ScrollData {
     sd
 }
 
+/// Centers the camera on the active actor whenever the AI takes over another
+/// actor (but not while the player drags the arena)
+fn follow_actor(sd: ScrollData, g: &CombatData, (w, h): (u32, u32)) -> ScrollData {
+    if sd.is_scrolling {
+        return sd;
+    }
+
+    match combat_screen::camera_focus(g) {
+        Some((id, _)) if sd.followed == Some(id) => sd,
+
+        Some((id, pos)) => {
+            let ScreenPos(x, y) = ScreenCoord::from_world_pos(pos).to_screen_pos(Camera {
+                offset: (0, 0),
+                ..sd.camera
+            });
+
+            ScrollData {
+                target: Some((w as i32 / 2 - x, h as i32 / 2 - y)),
+                followed: Some(id),
+                ..sd
+            }
+        }
+
+        None => ScrollData {
+            followed: None,
+            ..sd
+        },
+    }
+}
+
+/// Moves the camera while the mouse is at the edge of the window or else
+/// towards its target
+fn move_camera(sd: ScrollData) -> ScrollData {
+    let (ex, ey) = sd.edge_scrolling;
+
+    if (ex, ey) != (0, 0) {
+        return ScrollData {
+            camera: sd
+                .camera
+                .scroll(-ex * EDGE_SCROLL_SPEED, -ey * EDGE_SCROLL_SPEED),
+            target: None,
+            ..sd
+        };
+    }
+
+    match sd.target {
+        Some((tx, ty)) => {
+            let (x, y) = sd.camera.offset;
+            let (dx, dy) = ((tx - x) / 5, (ty - y) / 5);
+
+            if (dx, dy) == (0, 0) {
+                ScrollData {
+                    camera: Camera {
+                        offset: (tx, ty),
+                        ..sd.camera
+                    },
+                    target: None,
+                    ..sd
+                }
+            } else {
+                ScrollData {
+                    camera: sd.camera.scroll(dx, dy),
+                    ..sd
+                }
+            }
+        }
+
+        None => sd,
+    }
+}
+
 // fn get_scrolling(ui: &UI, game: &Game) -> Option<ScrollData> {
 //     if let Some
 // }
-fn draw_sprite(
+fn draw_sprite<T: RenderTarget>(
     pos: ScreenPos,
     align: Align,
     sprite: Sprite,
     tex: &mut Texture,
-    cvs: &mut WindowCanvas,
+    cvs: &mut Canvas<T>,
 ) -> Result<(), String> {
     let ((x, y), prev_frame, next_frame) = sprite.source;
     let (dx, dy) = sprite.offset;
@@ -232,14 +501,14 @@ fn draw_sprite(
     }
 }
 
-fn draw(
+fn draw<T: RenderTarget>(
     frame_pos: (i32, i32, u32, u32),
     prev_frame: Option<(f64, i32, i32)>,
     next_frame: Option<(f64, i32, i32)>,
     target_pos: (i32, i32, u32, u32),
     alpha: u8,
     tex: &mut Texture,
-    cvs: &mut WindowCanvas,
+    cvs: &mut Canvas<T>,
 ) -> Result<(), String> {
     let (x, y, w, h) = frame_pos;
     let to = Rect::new(target_pos.0, target_pos.1, target_pos.2, target_pos.3);
@@ -261,7 +530,7 @@ fn draw(
     Ok(())
 }
 
-fn draw_with_ration(
+fn draw_with_ration<T: RenderTarget>(
     frame_pos: (i32, i32, u32, u32),
     prev_frame: Option<(f64, i32, i32)>,
     next_frame: Option<(f64, i32, i32)>,
@@ -269,7 +538,7 @@ fn draw_with_ration(
     alpha: u8,
     angle: Direction,
     tex: &mut Texture,
-    cvs: &mut WindowCanvas,
+    cvs: &mut Canvas<T>,
 ) -> Result<(), String> {
     let (x, y, w, h) = frame_pos;
     let to = Rect::new(target_pos.0, target_pos.1, target_pos.2, target_pos.3);
@@ -0,0 +1,65 @@
+use crate::core::{DisplayStr, UserInput};
+use crate::ui::{ClickArea, ClickAreas, FontFace, Scene, ScreenPos, ScreenText, VideoSettings};
+
+const BTN_WIDTH: u32 = 600;
+const BTN_HEIGHT: u32 = 65;
+
+/// Lets the player change the video settings (on top of whatever screen is
+/// currently shown)
+pub fn render(
+    (_, _, viewport_width, _): (i32, i32, u32, u32),
+    settings: &VideoSettings,
+) -> (Scene, ClickAreas) {
+    let mut scene = Scene::empty();
+    let mut click_areas = vec![];
+    let x = (viewport_width as i32 - BTN_WIDTH as i32) / 2;
+    let mut y = 50;
+    let on_off = |b: bool| if b { "on" } else { "off" };
+    let (w, h) = settings.resolution;
+    let (next_w, next_h) = settings.next_resolution();
+
+    scene
+        .texts
+        .push(ScreenText::new(DisplayStr::new("Options"), ScreenPos(x, y)).font(FontFace::Big));
+
+    y += 2 * BTN_HEIGHT as i32;
+
+    let buttons = vec![
+        (
+            if settings.fullscreen {
+                format!("Resolution: {}x{} (windowed only)", w, h)
+            } else {
+                format!("Resolution: {}x{}", w, h)
+            },
+            UserInput::SetResolution(next_w, next_h),
+        ),
+        (
+            format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            UserInput::ToggleFullscreen,
+        ),
+        (
+            format!("Vsync: {} (after a restart)", on_off(settings.vsync)),
+            UserInput::ToggleVsync,
+        ),
+        ("Back".to_string(), UserInput::ToggleOptions),
+    ];
+
+    for (text, input) in buttons {
+        scene.texts.push(
+            ScreenText::new(DisplayStr::new(text), ScreenPos(x, y))
+                .padding(10)
+                .border(3, (23, 22, 21, 255))
+                .background((242, 241, 240, 255))
+                .width(BTN_WIDTH),
+        );
+
+        click_areas.push(ClickArea {
+            clipping_area: (x, y, BTN_WIDTH, BTN_HEIGHT),
+            action: Box::new(move |_| input.clone()),
+        });
+
+        y += BTN_HEIGHT as i32 + 10;
+    }
+
+    (scene, click_areas)
+}
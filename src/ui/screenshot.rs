@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use sdl2::image::SaveSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::surface::{Surface, SurfaceContext};
+
+use crate::core::{Game, TextureMap};
+use crate::ui::{render, AssetRepo, UI};
+
+/// Renders the game into an image (no window or GPU required) and writes it
+/// to the given PNG file
+pub fn render_to_png(ui: &UI, game: &Game, path: &Path) -> Result<(), String> {
+    let (_, _, w, h) = ui.viewport;
+
+    render_offscreen(w, h, |canvas, assets, _| {
+        render(canvas, ui, game, assets).map(|_| ())
+    })?
+    .save(path)
+}
+
+/// Loads the assets for an offscreen canvas of the given size and returns
+/// what the given function draws on it; the function also gets the sprite
+/// positions of the loaded combat graphics
+fn render_offscreen<F>(w: u32, h: u32, draw: F) -> Result<Surface<'static>, String>
+where
+    F: FnOnce(
+        &mut Canvas<Surface<'static>>,
+        &mut AssetRepo<SurfaceContext<'static>>,
+        TextureMap,
+    ) -> Result<(), String>,
+{
+    let mut canvas = Surface::new(w, h, PixelFormatEnum::RGBA32)?.into_canvas()?;
+    let texture_creator = canvas.texture_creator();
+    let mut assets = AssetRepo::new(&texture_creator).init(
+        Path::new("./assets/images"),
+        Path::new("./assets/fonts/font.ttf"),
+    )?;
+
+    let texture_map = assets.create_texture_from_path(Path::new("./assets/images/combat"))?;
+    draw(&mut canvas, &mut assets, texture_map)?;
+    drop(assets);
+
+    Ok(canvas.into_surface())
+}
+
+#[test]
+fn test_combat_screen_matches_golden_image() {
+    use sdl2::image::{InitFlag, LoadSurface};
+
+    use crate::core::{seed_rng, step, Headless, ObjectGenerator, UserInput};
+    use crate::ui::{init_ui, KeyBindings, VideoSettings};
+
+    // run `SDL_VIDEODRIVER=dummy UPDATE_GOLDEN=1 cargo test test_combat_screen`
+    // to replace the reference image after an intended change of the look
+    const GOLDEN_DIR: &str = "tests/golden";
+    // (fonts may be rendered slightly differently on other machines)
+    const MAX_CHANNEL_DIFF: i16 = 8;
+    const MAX_DIFFERING_PIXELS: usize = 1200 * 600 / 1000;
+
+    let _image_context = sdl2::image::init(InitFlag::PNG).unwrap();
+    let keys = KeyBindings::from_file(Path::new("assets/keys.ron")).unwrap();
+    let ui = init_ui((0, 0, 1200, 600), 1, keys, VideoSettings::default());
+    let golden_path = Path::new(GOLDEN_DIR).join("combat-screen.png");
+
+    seed_rng(42);
+
+    // the game has to know the sprites of the very assets it is drawn with
+    let image = render_offscreen(1200, 600, |canvas, assets, texture_map| {
+        let generator = ObjectGenerator::new(Path::new("assets/data/"));
+        let game = step(
+            Game::Start(generator, texture_map),
+            &Some(UserInput::NewGame),
+        );
+        let mut game = step(game, &Some(UserInput::SelectTeam(vec![])));
+        if let Game::Combat(data) = &mut game {
+            data.world.insert(Headless);
+        }
+
+        // play until the player has to assign the cards
+        for _ in 0..1000 {
+            match &game {
+                Game::Combat(data) if data.waiting_for().is_some() => break,
+                _ => game = step(game, &None),
+            }
+        }
+
+        render(canvas, &ui, &game, assets).map(|_| ())
+    })
+    .unwrap();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        image.save(&golden_path).unwrap();
+    }
+
+    let load = |p: &Path| {
+        Surface::from_file(p)
+            .and_then(|s| s.convert_format(PixelFormatEnum::RGBA32))
+            .unwrap_or_else(|e| panic!("Cannot load {:?}: {}", p, e))
+    };
+    let image = image.convert_format(PixelFormatEnum::RGBA32).unwrap();
+    let golden = load(&golden_path);
+
+    assert_eq!(image.size(), (1200, 600));
+    assert_eq!(image.size(), golden.size());
+
+    let num_differing_pixels = image.with_lock(|a| {
+        golden.with_lock(|b| {
+            a.chunks(4)
+                .zip(b.chunks(4))
+                .filter(|(p1, p2)| {
+                    p1.iter()
+                        .zip(p2.iter())
+                        .any(|(c1, c2)| (*c1 as i16 - *c2 as i16).abs() > MAX_CHANNEL_DIFF)
+                })
+                .count()
+        })
+    });
+
+    assert!(
+        num_differing_pixels <= MAX_DIFFERING_PIXELS,
+        "{} pixels differ from {:?}",
+        num_differing_pixels,
+        golden_path
+    );
+}
@@ -0,0 +1,103 @@
+use std::fs::File;
+use std::path::Path;
+
+use ron::de::from_reader;
+use serde::{Deserialize, Serialize};
+
+/// The window sizes the options screen offers
+pub const RESOLUTIONS: [(u32, u32); 5] = [
+    (1200, 600),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    /// The size of the window (ignored in fullscreen mode)
+    pub resolution: (u32, u32),
+    pub fullscreen: bool,
+    /// Caps the frame rate at the refresh rate of the monitor (only changes
+    /// after a restart)
+    pub vsync: bool,
+    /// The render drivers to try (in this order) until one works
+    pub renderers: Vec<String>,
+    /// The frame rate limit if there is no vsync
+    pub max_fps: u32,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        Self {
+            resolution: RESOLUTIONS[0],
+            fullscreen: false,
+            vsync: true,
+            renderers: vec![
+                "opengl".to_string(),
+                "opengles2".to_string(),
+                "software".to_string(),
+            ],
+            max_fps: 60,
+        }
+    }
+}
+
+impl VideoSettings {
+    /// Reads the settings from the given file (or uses the default settings if
+    /// there is no such file yet)
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let f = File::open(path).map_err(|e| e.to_string())?;
+        from_reader(f).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let txt = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+
+        std::fs::write(path, txt).map_err(|e| e.to_string())
+    }
+
+    /// The resolution which follows the current one in the list of offered
+    /// resolutions
+    pub fn next_resolution(&self) -> (u32, u32) {
+        let idx = RESOLUTIONS
+            .iter()
+            .position(|r| *r == self.resolution)
+            .map(|idx| (idx + 1) % RESOLUTIONS.len())
+            .unwrap_or(0);
+
+        RESOLUTIONS[idx]
+    }
+}
+
+#[test]
+fn test_settings_survive_a_restart() {
+    let path = std::env::temp_dir().join("arena-rl-test-settings.ron");
+    let settings = VideoSettings {
+        resolution: (1920, 1080),
+        fullscreen: true,
+        vsync: false,
+        ..VideoSettings::default()
+    };
+
+    settings.save(&path).unwrap();
+    let loaded = VideoSettings::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded, settings);
+    assert_eq!(loaded.next_resolution(), (2560, 1440));
+}
+
+#[test]
+fn test_missing_settings_use_the_defaults() {
+    let settings: VideoSettings = ron::from_str("(fullscreen: true)").unwrap();
+
+    assert!(settings.fullscreen);
+    assert_eq!(settings.renderers, VideoSettings::default().renderers);
+}
@@ -25,15 +25,56 @@ pub fn render(viewport: (i32, i32, u32, u32)) -> (Scene, ClickAreas) {
     ));
 
     scene.texts.push(ScreenText::new(
-        DisplayStr::new("Click somewhere to continue ..."),
+        DisplayStr::new("Click somewhere to start a single player game ..."),
         ScreenPos(xpos, viewport_height as i32 - 60),
     ));
 
-    (
-        scene,
-        vec![ClickArea {
-            clipping_area: viewport,
-            action: Box::new(|_| UserInput::NewGame),
-        }],
-    )
+    let mut click_areas = vec![];
+    let (btn_width, btn_height) = (260, 65);
+    let first_btn_y = ypos + height as i32 + 50;
+
+    let buttons = (2..=4)
+        .map(|n| {
+            (
+                format!("Hot-seat: {} players", n),
+                UserInput::NewHotSeatGame(n),
+            )
+        })
+        .chain(Some((
+            "Three-way battle".to_string(),
+            UserInput::NewThreeWayBattle,
+        )))
+        .chain(Some(("Options".to_string(), UserInput::ToggleOptions)))
+        .collect::<Vec<_>>();
+
+    // (as many buttons per row as fit into the window)
+    let per_row = (viewport_width as i32 / btn_width).max(1);
+    let num_columns = (buttons.len() as i32).min(per_row);
+
+    for (idx, (text, input)) in buttons.into_iter().enumerate() {
+        let (row, column) = (idx as i32 / per_row, idx as i32 % per_row);
+        let btn_x = (viewport_width as i32 - num_columns * btn_width) / 2 + column * btn_width;
+        let btn_y = first_btn_y + row * btn_height as i32;
+
+        scene.texts.push(
+            ScreenText::new(DisplayStr::new(text), ScreenPos(btn_x, btn_y))
+                .padding(10)
+                .border(3, (23, 22, 21, 255))
+                .background((242, 241, 240, 255))
+                .width(btn_width as u32),
+        );
+
+        click_areas.push(ClickArea {
+            clipping_area: (btn_x, btn_y, btn_width as u32, btn_height),
+            action: Box::new(move |_| input.clone()),
+        });
+    }
+
+    // anywhere else starts a single player game
+    click_areas.push(ClickArea {
+        clipping_area: viewport,
+        action: Box::new(|_| UserInput::NewGame),
+    });
+
+    (scene, click_areas)
 }
@@ -4,7 +4,7 @@ use std::string::ToString;
 
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator, WindowCanvas};
+use sdl2::render::{BlendMode, Canvas, RenderTarget, Texture, TextureCreator};
 use sdl2::surface::Surface;
 use sdl2::ttf::Font as Sdl2Font;
 use sdl2::video::WindowContext;
@@ -13,13 +13,13 @@ use crate::ui::types::{Align, ScreenPos, ScreenText};
 
 const ASCII: &str = " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
 
-pub struct Font<'a> {
+pub struct Font<'a, C = WindowContext> {
     font_canvas: Canvas<Surface<'a>>,
     glyphs: Vec<GlyphRegion>,
     line_height: u32,
     space_advance: i32,
 
-    texture_creator: &'a TextureCreator<WindowContext>,
+    texture_creator: &'a TextureCreator<C>,
     cached_texts: HashMap<String, (Texture<'a>, u32, u32)>,
 }
 
@@ -30,9 +30,9 @@ struct GlyphRegion {
     height: u32,
 }
 
-impl<'a> Font<'a> {
+impl<'a, C> Font<'a, C> {
     pub fn from_font(
-        texture_creator: &'a TextureCreator<sdl2::video::WindowContext>,
+        texture_creator: &'a TextureCreator<C>,
         font: Sdl2Font,
     ) -> Result<Self, String> {
         let mut total_width = 0;
@@ -100,7 +100,11 @@ impl<'a> Font<'a> {
         })
     }
 
-    pub fn draw(&mut self, screen_txt: ScreenText, cvs: &mut WindowCanvas) -> Result<(), String> {
+    pub fn draw<T: RenderTarget>(
+        &mut self,
+        screen_txt: ScreenText,
+        cvs: &mut Canvas<T>,
+    ) -> Result<(), String> {
         let cache_key = screen_txt.text.to_string();
 
         if let Some((ref mut tex, w, h)) = self.cached_texts.get_mut(&cache_key) {
@@ -114,9 +118,11 @@ impl<'a> Font<'a> {
 
         let pos = screen_txt.pos;
         let align = screen_txt.align;
+        let scale = screen_txt.scale;
         let prepared_text = prepare(screen_txt, self);
         let (w, h) = prepared_text.dim;
-        let ScreenPos(x, y) = pos.align(align, w, h);
+        let (tw, th) = scale_dim(scale, w, h);
+        let ScreenPos(x, y) = pos.align(align, tw, th);
         let pixel_format = self.texture_creator.default_pixel_format();
         // let pixel_format = sdl2::pixels::PixelFormatEnum::ARGB32;
 
@@ -136,7 +142,7 @@ impl<'a> Font<'a> {
             .map_err(to_string)?;
 
         // actually draw the text texture
-        cvs.copy(&target_tex, Rect::new(0, 0, w, h), Rect::new(x, y, w, h))?;
+        cvs.copy(&target_tex, Rect::new(0, 0, w, h), Rect::new(x, y, tw, th))?;
 
         // cache the created texture for future frames
         self.cached_texts.insert(cache_key, (target_tex, w, h));
@@ -200,7 +206,7 @@ struct PreparedText {
     border: Option<(u32, Color)>,
 }
 
-fn prepare<'a>(text: ScreenText, font: &'a Font) -> PreparedText {
+fn prepare<C>(text: ScreenText, font: &Font<C>) -> PreparedText {
     let (mut x, mut y) = (0, 0);
     let mut lines = Vec::new();
     let mut text_width: u32 = 0;
@@ -359,14 +365,16 @@ fn scale_dim(scale_factor: f32, w: u32, h: u32) -> (u32, u32) {
 
 fn align_line_horizontal(a: Align, line_width: u32, text_width: u32) -> i32 {
     match a {
-        Align::TopLeft => 0,
+        Align::TopLeft | Align::BottomLeft => 0,
         Align::MidCenter => (text_width - line_width) as i32 / 2,
+        Align::TopRight | Align::BottomRight => (text_width - line_width) as i32,
     }
 }
 
 fn align_line_vertical(a: Align, text_height: u32, max_height: u32) -> i32 {
     match a {
-        Align::TopLeft => 0,
+        Align::TopLeft | Align::TopRight => 0,
         Align::MidCenter => (max_height - text_height) as i32 / 2,
+        Align::BottomLeft | Align::BottomRight => (max_height - text_height) as i32,
     }
 }
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 
-use crate::core::{DisplayStr, Sprite, UserInput, WorldPos};
+use crate::core::{DisplayStr, Sprite, UserInput, WorldPos, ID};
 
 pub const TILE_WIDTH: u32 = 128;
 pub const TILE_HEIGHT: u32 = 128;
@@ -42,8 +42,12 @@ impl ScreenCoord {
         WorldPos::new(x, y, z)
     }
 
-    pub fn to_screen_pos(&self, scroll_offset: (i32, i32)) -> ScreenPos {
-        ScreenPos(self.0 + scroll_offset.0, self.1 + scroll_offset.1 + self.2)
+    pub fn to_screen_pos(&self, camera: Camera) -> ScreenPos {
+        let (dx, dy) = camera.offset;
+        let x = self.0 as f32 * camera.zoom;
+        let y = (self.1 + self.2) as f32 * camera.zoom;
+
+        ScreenPos(x.round() as i32 + dx, y.round() as i32 + dy)
     }
 
     pub fn translate(self, dx: i32, dy: i32, dz: i32) -> ScreenCoord {
@@ -66,14 +70,65 @@ impl ScreenCoord {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ScreenPos(pub i32, pub i32);
 
+/// Which part of the arena is visible (the offset is in screen pixels)
+#[derive(Clone, Copy, Debug)]
+pub struct Camera {
+    pub offset: (i32, i32),
+    pub zoom: f32,
+}
+
+impl Camera {
+    pub const MIN_ZOOM: f32 = 0.25;
+    pub const MAX_ZOOM: f32 = 2.0;
+
+    pub fn new(offset: (i32, i32)) -> Self {
+        Self { offset, zoom: 1.0 }
+    }
+
+    /// The inverse of ScreenCoord::to_screen_pos (ignoring the z coordinate)
+    pub fn to_screen_coord(self, ScreenPos(x, y): ScreenPos) -> ScreenCoord {
+        let (dx, dy) = self.offset;
+
+        ScreenCoord::new(
+            ((x - dx) as f32 / self.zoom).round() as i32,
+            ((y - dy) as f32 / self.zoom).round() as i32,
+        )
+    }
+
+    /// Changes the zoom while the given screen position keeps showing the
+    /// same part of the arena
+    pub fn zoom_at(self, zoom: f32, p: ScreenPos) -> Self {
+        let zoom = zoom.clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        let ScreenCoord(x, y, _) = self.to_screen_coord(p);
+        let camera = Self { zoom, ..self };
+        let ScreenPos(x, y) = ScreenCoord(x, y, 0).to_screen_pos(camera);
+
+        camera.scroll(p.0 - x, p.1 - y)
+    }
+
+    pub fn scroll(self, dx: i32, dy: i32) -> Self {
+        Self {
+            offset: (self.offset.0 + dx, self.offset.1 + dy),
+            ..self
+        }
+    }
+}
+
 impl ScreenPos {
+    pub fn is_inside(self, (x, y, w, h): (i32, i32, u32, u32)) -> bool {
+        self.0 >= x && self.0 < x + w as i32 && self.1 >= y && self.1 < y + h as i32
+    }
+
     pub fn align(self, align: Align, w: u32, h: u32) -> Self {
         match align {
             Align::TopLeft => self,
+            Align::TopRight => Self(self.0 - w as i32, self.1),
             Align::MidCenter => Self(self.0 - w as i32 / 2, self.1 - h as i32 / 2),
+            Align::BottomLeft => Self(self.0, self.1 - h as i32),
+            Align::BottomRight => Self(self.0 - w as i32, self.1 - h as i32),
         }
     }
 }
@@ -86,6 +141,16 @@ fn mapping_between_world_and_screen_coordinates_is_isomorphic() {
     assert_eq!(wp.as_xy(), sc.to_world_pos().as_xy());
 }
 
+#[test]
+fn zooming_keeps_the_pointed_at_position_in_place() {
+    let camera = Camera::new((300, -200)).zoom_at(0.5, ScreenPos(640, 360));
+    let sc = ScreenCoord::from_world_pos(WorldPos::new(7.0, 3.0, 0.0));
+    let p = sc.to_screen_pos(camera);
+
+    assert_eq!(camera.zoom_at(1.5, p).to_screen_coord(p).0, sc.0);
+    assert_eq!(camera.zoom_at(1.5, p).to_screen_coord(p).1, sc.1);
+}
+
 pub struct ClickArea {
     pub clipping_area: (i32, i32, u32, u32),
     pub action: Box<dyn Fn(ScreenPos) -> UserInput>,
@@ -100,12 +165,31 @@ pub struct UI {
     pub frames: u32,
     pub last_check: std::time::Instant,
     pub scrolling: Option<ScrollData>,
+    /// The number of the latest combat log events which are skipped to show
+    /// older ones
+    pub log_offset: usize,
+    /// Shows all cards of the deck instead of the arena
+    pub show_deck: bool,
+    pub show_minimap: bool,
+    pub keys: super::KeyBindings,
+    pub settings: super::VideoSettings,
+    /// Shows the video settings instead of the current screen
+    pub show_options: bool,
+    /// Where the mouse points at (e.g. to show a tooltip)
+    pub mouse_pos: Option<ScreenPos>,
 }
 
 pub struct ScrollData {
     pub is_scrolling: bool,
     pub has_scrolled: bool,
-    pub offset: (i32, i32),
+    pub camera: Camera,
+    /// The direction in which the camera moves while the mouse is at the
+    /// edge of the window
+    pub edge_scrolling: (i32, i32),
+    /// The camera glides to this offset (e.g. to follow an AI actor)
+    pub target: Option<(i32, i32)>,
+    /// The last actor the camera has followed
+    pub followed: Option<ID>,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -216,12 +300,21 @@ impl ScreenText {
     }
 }
 
+/// A filled rectangle: area and color
+pub type ScreenRect = ((i32, i32, u32, u32), (u8, u8, u8, u8));
+
 #[derive(Debug)]
 pub struct Scene {
     pub background: (u8, u8, u8),
     pub texts: Vec<ScreenText>,
     pub sprites: Vec<ScreenSprite>,
     pub images: Vec<(String, ScreenSprite)>,
+    /// Filled rectangles (area, color) which are drawn on top of the sprites
+    /// (but below the texts)
+    pub rects: Vec<ScreenRect>,
+    /// The descriptions which are shown when the mouse points at their area
+    /// (the first one wins)
+    pub tooltips: Vec<Tooltip>,
 }
 
 impl Scene {
@@ -231,6 +324,8 @@ impl Scene {
             texts: vec![],
             sprites: Vec::with_capacity(500),
             images: vec![],
+            rects: vec![],
+            tooltips: vec![],
         }
     }
 
@@ -246,13 +341,25 @@ impl Scene {
 pub enum Align {
     TopLeft,
     // TopCenter,
-    // TopRight,
+    TopRight,
     // MidLeft,
     MidCenter,
     // MidRight,
-    // BottomLeft,
+    BottomLeft,
     // BottomCenter,
-    // BottomRight,
+    BottomRight,
+}
+
+#[derive(Debug)]
+pub struct Tooltip {
+    pub area: (i32, i32, u32, u32),
+    pub text: DisplayStr,
+}
+
+impl Tooltip {
+    pub fn contains(&self, p: ScreenPos) -> bool {
+        p.is_inside(self.area)
+    }
 }
 
 #[derive(Debug)]